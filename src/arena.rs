@@ -0,0 +1,166 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use tracing::{error, info, warn};
+
+// File the game writes into the replays folder while a battle is running
+pub const ARENA_INFO_FILE: &str = "tempArenaInfo.json";
+
+// Relation of a vehicle to the local player, as written by the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    Own,
+    Ally,
+    Enemy,
+}
+
+impl Relation {
+    fn from_raw(value: u8) -> Self {
+        match value {
+            0 => Relation::Own,
+            1 => Relation::Ally,
+            _ => Relation::Enemy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ArenaVehicle {
+    pub name: String,
+    #[serde(rename = "shipId")]
+    pub ship_id: u64,
+    relation: u8,
+    // Avatar (entity) id inside this battle, not the player's account id
+    pub id: i64,
+    // Not written by every game version, 0 or missing for solo players
+    #[serde(rename = "prebattleId", default)]
+    prebattle_id: Option<i64>,
+}
+
+impl ArenaVehicle {
+    pub fn relation(&self) -> Relation {
+        Relation::from_raw(self.relation)
+    }
+
+    // Players in the same division share a pre-battle id
+    pub fn division(&self) -> Option<i64> {
+        self.prebattle_id.filter(|&id| id > 0)
+    }
+
+    // Co-op and operation bots are named with a localization key like ":IDS_..."
+    pub fn is_bot(&self) -> bool {
+        self.name.starts_with(':')
+    }
+}
+
+// Same layout is used for the leading JSON block of a replay
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArenaInfo {
+    pub vehicles: Vec<ArenaVehicle>,
+    #[serde(default)]
+    pub client_version_from_exe: String,
+    #[serde(default)]
+    pub map_display_name: String,
+    #[serde(default)]
+    pub match_group: String,
+    #[serde(default)]
+    pub player_name: String,
+    #[serde(default)]
+    pub player_vehicle: String,
+    #[serde(default)]
+    pub date_time: String,
+}
+
+impl ArenaInfo {
+    pub fn load(path: &Path) -> Option<Self> {
+        info!("Loading arena info from: {:?}", path);
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("No arena info available: {}", e);
+                return None;
+            }
+        };
+
+        match serde_json::from_str::<Self>(&contents) {
+            Ok(arena) => {
+                info!("Loaded arena info with {} vehicles", arena.vehicles.len());
+                Some(arena)
+            }
+            Err(e) => {
+                error!("Error parsing arena info: {}", e);
+                None
+            }
+        }
+    }
+
+    // None when we play solo, or when the game version doesn't write pre-battle ids
+    // at all, in which case divisions can't be told apart from solo players
+    pub fn own_division(&self) -> Option<i64> {
+        if self
+            .vehicles
+            .iter()
+            .all(|vehicle| vehicle.prebattle_id.is_none())
+        {
+            info!("Arena info has no pre-battle ids, divisions are not shown");
+            return None;
+        }
+        self.vehicles
+            .iter()
+            .find(|vehicle| vehicle.relation() == Relation::Own)?
+            .division()
+    }
+
+    // Split vehicles into (allies, enemies); our own vehicle goes with the allies
+    pub fn split_teams(&self) -> (Vec<&ArenaVehicle>, Vec<&ArenaVehicle>) {
+        self.vehicles
+            .iter()
+            .partition(|vehicle| vehicle.relation() != Relation::Enemy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arena(vehicles: &str) -> ArenaInfo {
+        serde_json::from_str(&format!(
+            r#"{{"playerName": "Tester", "vehicles": [{}]}}"#,
+            vehicles
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn division_mates_share_our_prebattle_id() {
+        let arena = arena(
+            r#"{"shipId": 1, "relation": 0, "id": 11, "name": "Tester", "prebattleId": 7301},
+               {"shipId": 2, "relation": 1, "id": 12, "name": "Wingman", "prebattleId": 7301},
+               {"shipId": 3, "relation": 1, "id": 13, "name": "Solo", "prebattleId": 0},
+               {"shipId": 4, "relation": 2, "id": 14, "name": "Enemy", "prebattleId": 9000}"#,
+        );
+        assert_eq!(arena.own_division(), Some(7301));
+        let divisions: Vec<Option<i64>> = arena.vehicles.iter().map(|v| v.division()).collect();
+        assert_eq!(divisions, [Some(7301), Some(7301), None, Some(9000)]);
+    }
+
+    #[test]
+    fn solo_players_have_no_division() {
+        let arena = arena(
+            r#"{"shipId": 1, "relation": 0, "id": 11, "name": "Tester", "prebattleId": 0},
+               {"shipId": 2, "relation": 1, "id": 12, "name": "Ally", "prebattleId": 0}"#,
+        );
+        assert_eq!(arena.own_division(), None);
+    }
+
+    #[test]
+    fn missing_prebattle_ids_disable_divisions() {
+        let arena = arena(
+            r#"{"shipId": 1, "relation": 0, "id": 11, "name": "Tester"},
+               {"shipId": 2, "relation": 1, "id": 12, "name": "Ally"}"#,
+        );
+        assert_eq!(arena.own_division(), None);
+    }
+}
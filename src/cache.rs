@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::region::Region;
+use crate::snapshots::{RecentStats, SnapshotStore, StatsWindow};
+use crate::stats::{self, PlayerStats, StatsError, StatsProvider};
+
+pub const STATS_CACHE_FILE: &str = "stats_cache.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    // Unix timestamp in seconds
+    fetched_at: u64,
+    stats: PlayerStats,
+}
+
+// Stats as shown on the card, `stale` when the provider failed and an expired entry was used
+#[derive(Debug, Clone)]
+pub struct CachedStats {
+    pub stats: PlayerStats,
+    pub stale: bool,
+    // Windows we have an older snapshot for
+    pub recent: Vec<(StatsWindow, RecentStats)>,
+}
+
+pub struct StatsCache {
+    path: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+    ttl_secs: u64,
+    max_entries: usize,
+    // Entries changed since the last save
+    dirty: bool,
+}
+
+impl StatsCache {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(STATS_CACHE_FILE)
+    }
+
+    pub fn load(path: &Path, ttl_secs: u64, max_entries: usize) -> Self {
+        info!("Loading stats cache from: {:?}", path);
+
+        let entries = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("Error parsing stats cache: {}", e);
+                HashMap::new()
+            }),
+            Err(e) => {
+                warn!("No stats cache found: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            entries,
+            ttl_secs,
+            max_entries,
+            dirty: false,
+        }
+    }
+
+    // Rewrites the whole file, so callers save once per batch of lookups
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&self.entries) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(&self.path, contents) {
+                    error!("Error saving stats cache: {}", e);
+                }
+            }
+            Err(e) => error!("Error serializing stats cache: {}", e),
+        }
+    }
+
+    // Keyed by name, the only thing we know about a player before the lookup
+    fn key(region: Region, name: &str, ship_id: u64) -> String {
+        format!(
+            "{}:{}:{}",
+            region.code(),
+            name.to_ascii_lowercase(),
+            ship_id
+        )
+    }
+
+    pub fn get(&self, region: Region, name: &str, ship_id: u64) -> Option<CachedStats> {
+        let entry = self.entries.get(&Self::key(region, name, ship_id))?;
+        let age = now_secs().saturating_sub(entry.fetched_at);
+        Some(CachedStats {
+            stats: entry.stats.clone(),
+            stale: age > self.ttl_secs,
+            recent: Vec::new(),
+        })
+    }
+
+    pub fn insert(&mut self, region: Region, name: &str, ship_id: u64, stats: PlayerStats) {
+        let key = Self::key(region, name, ship_id);
+        self.entries.insert(
+            key,
+            CacheEntry {
+                fetched_at: now_secs(),
+                stats,
+            },
+        );
+        self.dirty = true;
+        self.evict();
+    }
+
+    // Drop the oldest entries once the cache grows past its cap
+    fn evict(&mut self) {
+        if self.entries.len() <= self.max_entries {
+            return;
+        }
+        let mut by_age: Vec<(u64, String)> = self
+            .entries
+            .iter()
+            .map(|(key, entry)| (entry.fetched_at, key.clone()))
+            .collect();
+        by_age.sort();
+
+        let excess = self.entries.len() - self.max_entries;
+        for (_, key) in by_age.into_iter().take(excess) {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+// Serve fresh cache entries directly, otherwise ask the provider and fall back
+// to the expired entry when it fails. Fresh lookups are also kept as snapshots
+// for the recent-performance windows. Nothing is written to disk here, see
+// `StatsCache::save` and `SnapshotStore::save`.
+pub fn fetch_cached(
+    provider: &dyn StatsProvider,
+    cache: &Mutex<StatsCache>,
+    snapshots: &Mutex<SnapshotStore>,
+    region: Region,
+    name: &str,
+    ship_id: u64,
+) -> Result<CachedStats, StatsError> {
+    let cached = lock(cache).get(region, name, ship_id);
+    let mut result = match cached {
+        Some(cached) if !cached.stale => cached,
+        cached => match stats::fetch_player_stats(provider, region, name, ship_id) {
+            Ok(stats) => {
+                lock(cache).insert(region, name, ship_id, stats.clone());
+                lock(snapshots).record(region, ship_id, &stats, now_secs());
+                CachedStats {
+                    stats,
+                    stale: false,
+                    recent: Vec::new(),
+                }
+            }
+            Err(e) => match cached {
+                Some(cached) => {
+                    warn!("Using stale stats for {}: {}", name, e);
+                    cached
+                }
+                None => return Err(e),
+            },
+        },
+    };
+
+    let snapshots = lock(snapshots);
+    result.recent = StatsWindow::ALL
+        .iter()
+        .filter_map(|&window| {
+            let recent = snapshots.recent(region, ship_id, &result.stats, window, now_secs())?;
+            Some((window, recent))
+        })
+        .collect();
+    Ok(result)
+}
+
+// A lookup that panicked while holding the lock leaves the data itself intact
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ships::ShipInfo;
+    use crate::stats::{BattleStats, ClanInfo};
+
+    const TTL: u64 = 3600;
+
+    // Provider that always answers with `battles` account battles, or fails
+    struct Fake {
+        battles: Option<u32>,
+    }
+
+    impl StatsProvider for Fake {
+        fn find_account_id(&self, _: Region, _: &str) -> Result<i64, StatsError> {
+            self.battles
+                .map(|_| 42)
+                .ok_or_else(|| StatsError::Http("offline".to_string()))
+        }
+
+        fn account_stats(&self, _: Region, _: i64) -> Result<BattleStats, StatsError> {
+            Ok(BattleStats {
+                battles: self.battles.unwrap_or_default(),
+                ..Default::default()
+            })
+        }
+
+        fn ship_stats(&self, _: Region, _: i64, _: u64) -> Result<BattleStats, StatsError> {
+            Ok(BattleStats::default())
+        }
+
+        fn all_ship_stats(&self, _: Region, _: i64) -> Result<Vec<(u64, BattleStats)>, StatsError> {
+            Ok(Vec::new())
+        }
+
+        fn clan(&self, _: Region, _: i64) -> Result<Option<ClanInfo>, StatsError> {
+            Ok(None)
+        }
+
+        fn ship_page(&self, _: Region, _: u32) -> Result<(Vec<(u64, ShipInfo)>, u32), StatsError> {
+            Ok((Vec::new(), 1))
+        }
+    }
+
+    fn cache(max_entries: usize) -> Mutex<StatsCache> {
+        Mutex::new(StatsCache::load(
+            Path::new("/nonexistent/stats_cache.json"),
+            TTL,
+            max_entries,
+        ))
+    }
+
+    fn snapshots() -> Mutex<SnapshotStore> {
+        Mutex::new(SnapshotStore::load(Path::new(
+            "/nonexistent/stats_snapshots.json",
+        )))
+    }
+
+    fn stats(battles: u32) -> PlayerStats {
+        PlayerStats {
+            account_id: 42,
+            account: BattleStats {
+                battles,
+                ..Default::default()
+            },
+            ship: BattleStats::default(),
+            clan: None,
+        }
+    }
+
+    fn age(cache: &Mutex<StatsCache>, name: &str, secs: u64) {
+        let mut cache = lock(cache);
+        let entry = cache
+            .entries
+            .get_mut(&StatsCache::key(Region::Eu, name, 1))
+            .unwrap();
+        entry.fetched_at -= secs;
+    }
+
+    fn fetch(provider: &Fake, cache: &Mutex<StatsCache>) -> Result<CachedStats, StatsError> {
+        fetch_cached(provider, cache, &snapshots(), Region::Eu, "Alpha", 1)
+    }
+
+    #[test]
+    fn fresh_entries_are_served_without_asking_the_provider() {
+        let cache = cache(10);
+        lock(&cache).insert(Region::Eu, "alpha", 1, stats(100));
+
+        let result = fetch(&Fake { battles: Some(200) }, &cache).unwrap();
+        assert_eq!(result.stats.account.battles, 100);
+        assert!(!result.stale);
+    }
+
+    #[test]
+    fn entries_past_the_ttl_are_fetched_again() {
+        let cache = cache(10);
+        lock(&cache).insert(Region::Eu, "Alpha", 1, stats(100));
+        age(&cache, "Alpha", TTL + 1);
+        assert!(lock(&cache).get(Region::Eu, "Alpha", 1).unwrap().stale);
+
+        let result = fetch(&Fake { battles: Some(200) }, &cache).unwrap();
+        assert_eq!(result.stats.account.battles, 200);
+        assert!(!result.stale);
+        assert!(!lock(&cache).get(Region::Eu, "Alpha", 1).unwrap().stale);
+    }
+
+    #[test]
+    fn expired_entries_stand_in_when_the_provider_fails() {
+        let cache = cache(10);
+        let offline = Fake { battles: None };
+        assert!(fetch(&offline, &cache).is_err());
+
+        lock(&cache).insert(Region::Eu, "Alpha", 1, stats(100));
+        age(&cache, "Alpha", TTL + 1);
+        let result = fetch(&offline, &cache).unwrap();
+        assert_eq!(result.stats.account.battles, 100);
+        assert!(result.stale);
+    }
+
+    #[test]
+    fn the_oldest_entries_are_evicted_past_the_cap() {
+        let cache = cache(2);
+        let mut guard = lock(&cache);
+        guard.insert(Region::Eu, "Alpha", 1, stats(1));
+        guard.insert(Region::Eu, "Bravo", 1, stats(2));
+        drop(guard);
+        age(&cache, "Alpha", 60);
+
+        let mut guard = lock(&cache);
+        guard.insert(Region::Eu, "Charlie", 1, stats(3));
+        assert!(guard.get(Region::Eu, "Alpha", 1).is_none());
+        assert!(guard.get(Region::Eu, "Bravo", 1).is_some());
+        assert!(guard.get(Region::Eu, "Charlie", 1).is_some());
+    }
+}
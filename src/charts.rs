@@ -0,0 +1,137 @@
+use iced::mouse;
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke};
+use iced::{alignment, Color, Point, Rectangle, Renderer, Size, Theme};
+
+use crate::colors::*;
+
+// Room for the labels under the bars and the values above them
+const LABEL_HEIGHT: f32 = 18.0;
+
+pub struct Bar {
+    pub label: String,
+    pub value: f32,
+    pub value_label: String,
+    pub color: Color,
+}
+
+// Vertical bars scaled to the largest value
+pub struct BarChart {
+    pub bars: Vec<Bar>,
+}
+
+impl<Message> canvas::Program<Message> for BarChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let max = self.bars.iter().map(|bar| bar.value).fold(0.0, f32::max);
+        if self.bars.is_empty() || max <= 0.0 {
+            return vec![frame.into_geometry()];
+        }
+
+        let slot = bounds.width / self.bars.len() as f32;
+        let plot_height = bounds.height - 2.0 * LABEL_HEIGHT;
+        for (index, bar) in self.bars.iter().enumerate() {
+            let center = slot * (index as f32 + 0.5);
+            let height = plot_height * bar.value / max;
+            let top = LABEL_HEIGHT + plot_height - height;
+            frame.fill_rectangle(
+                Point::new(center - slot * 0.3, top),
+                Size::new(slot * 0.6, height),
+                bar.color,
+            );
+            frame.fill_text(label(
+                &bar.value_label,
+                Point::new(center, top - 2.0),
+                TEXT_COLOR,
+            ));
+            frame.fill_text(label(
+                &bar.label,
+                Point::new(center, bounds.height),
+                GRAY_COLOR,
+            ));
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+// A single series with its min and max written on the left
+pub struct LineChart {
+    pub points: Vec<f32>,
+    pub color: Color,
+}
+
+impl<Message> canvas::Program<Message> for LineChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        if self.points.len() < 2 {
+            return vec![frame.into_geometry()];
+        }
+
+        let min = self.points.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = self
+            .points
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max);
+        // Keep flat lines in the middle instead of dividing by zero
+        let range = (max - min).max(0.1);
+        let left = 50.0;
+        let width = bounds.width - left;
+        let height = bounds.height - LABEL_HEIGHT;
+        let step = width / (self.points.len() - 1) as f32;
+        let position = |index: usize, value: f32| {
+            Point::new(
+                left + step * index as f32,
+                LABEL_HEIGHT / 2.0 + height * (1.0 - (value - min) / range),
+            )
+        };
+
+        let line = Path::new(|builder| {
+            builder.move_to(position(0, self.points[0]));
+            for (index, &value) in self.points.iter().enumerate().skip(1) {
+                builder.line_to(position(index, value));
+            }
+        });
+        frame.stroke(
+            &line,
+            Stroke::default().with_color(self.color).with_width(2.0),
+        );
+
+        for (value, y) in [(max, LABEL_HEIGHT), (min, bounds.height)] {
+            let mut text = label(&format!("{:.1}%", value), Point::new(0.0, y), GRAY_COLOR);
+            text.horizontal_alignment = alignment::Horizontal::Left;
+            frame.fill_text(text);
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+// Centered text sitting on `position`
+fn label(content: &str, position: Point, color: Color) -> canvas::Text {
+    canvas::Text {
+        content: content.to_string(),
+        position,
+        color,
+        size: 12.0,
+        horizontal_alignment: alignment::Horizontal::Center,
+        vertical_alignment: alignment::Vertical::Bottom,
+        ..Default::default()
+    }
+}
@@ -5,8 +5,6 @@ pub const TEXT_COLOR: Color = Color::from_rgb(0.9, 0.9, 0.9); // #E6E6E6
 
 pub const GRAY_COLOR: Color = Color::from_rgb(0.7, 0.7, 0.7); // #B3B3B3
 
-pub const ORANGE_COLOR: Color = Color::from_rgb(1.0, 0.6, 0.0); // #FFA500
-
 pub const RED_COLOR: Color = Color::from_rgb(0.898, 0.325, 0.294); // #E5534B
 
 pub const GREEN_COLOR: Color = Color::from_rgb(0.204, 0.490, 0.224); // #347D39
@@ -22,6 +20,22 @@ pub const DISCORD_BLACK: Color = Color::from_rgb(0.0, 0.0, 0.0);
 
 pub const DISCORD_TOP_BAR: Color = Color::from_rgb(0.15, 0.15, 0.15);
 
+pub const OWN_CARD_COLOR: Color = Color::from_rgb(0.20, 0.19, 0.14); // #333024 - warm tint for our own card
+
+pub const OWN_BORDER_COLOR: Color = Color::from_rgb(0.85, 0.70, 0.25); // #D9B340
+
+pub const FRIEND_BORDER_COLOR: Color = Color::from_rgb(0.30, 0.75, 0.45); // #4DBF73
+
+pub const DIVISION_BORDER_COLOR: Color = Color::from_rgb(0.45, 0.55, 0.95); // #738CF2
+
+pub const BLACKLIST_CARD_COLOR: Color = Color::from_rgb(0.22, 0.13, 0.13); // #382121 - red tint for blacklisted players
+
+pub const BLACKLIST_BORDER_COLOR: Color = Color::from_rgb(0.85, 0.25, 0.22); // #D94038
+
+pub const NOTE_COLOR: Color = Color::from_rgb(0.95, 0.80, 0.35); // #F2CC59 - players we wrote a note about
+
+pub const CLAN_TAG_COLOR: Color = Color::from_rgb(0.6, 0.7, 0.8); // #99B3CC - every clan, the API has no clan colours
+
 pub const DISCORD_BLUE: Color = Color::from_rgb(0.0, 0.5, 1.0); // #6699FF - Discord's link color
 
 pub const DISCORD_TEXT: Color = Color::from_rgb(0.9, 0.9, 0.9);
@@ -35,3 +49,20 @@ pub const LIGHT_RED_COLOR: Color = Color::from_rgb(0.949, 0.400, 0.369); // #F26
 pub const DARK_GREEN_COLOR: Color = Color::from_rgb(0.153, 0.392, 0.173); // #27632B
 
 pub const DARK_RED_COLOR: Color = Color::from_rgb(0.847, 0.250, 0.219); // #D84038
+
+// Community rating tiers (wows-numbers)
+pub const RATING_BAD_COLOR: Color = Color::from_rgb(0.996, 0.055, 0.0); // #FE0E00
+
+pub const RATING_BELOW_AVERAGE_COLOR: Color = Color::from_rgb(0.996, 0.475, 0.012); // #FE7903
+
+pub const RATING_AVERAGE_COLOR: Color = Color::from_rgb(1.0, 0.780, 0.122); // #FFC71F
+
+pub const RATING_GOOD_COLOR: Color = Color::from_rgb(0.267, 0.702, 0.0); // #44B300
+
+pub const RATING_VERY_GOOD_COLOR: Color = Color::from_rgb(0.192, 0.502, 0.0); // #318000
+
+pub const RATING_GREAT_COLOR: Color = Color::from_rgb(0.008, 0.788, 0.702); // #02C9B3
+
+pub const RATING_UNICUM_COLOR: Color = Color::from_rgb(0.816, 0.259, 0.953); // #D042F3
+
+pub const RATING_SUPER_UNICUM_COLOR: Color = Color::from_rgb(0.627, 0.051, 0.773); // #A00DC5
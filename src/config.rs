@@ -3,11 +3,78 @@ use std::fs;
 use std::path::PathBuf;
 use tracing::{error, info, warn};
 
+use crate::prediction::WinModel;
+use crate::profile::ProfileSite;
+use crate::region::Region;
+use crate::snapshots::StatsWindow;
+use crate::team_sort::TeamSortKey;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub window_width: u32,
     pub window_height: u32,
     pub selected_folder: Option<String>,
+    #[serde(default)]
+    pub region: Region,
+    #[serde(default)]
+    pub profile_site: ProfileSite,
+    // Link template for `ProfileSite::Custom`, supports {region}, {account_id} and {name}
+    #[serde(default)]
+    pub custom_profile_url: String,
+    // Link template for `ProfileSite::Custom`, supports {region}, {clan_id} and {tag}
+    #[serde(default)]
+    pub custom_clan_url: String,
+    // Wargaming developer application id used for stats lookups
+    #[serde(default)]
+    pub application_id: String,
+    // Replaces the regional API host, mostly useful for testing
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+    // Budget shared by all stats lookups, the public API allows 10 for client apps
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: u32,
+    // Cached stats older than this are refetched, but still shown when offline
+    #[serde(default = "default_cache_ttl_hours")]
+    pub cache_ttl_hours: u64,
+    #[serde(default = "default_cache_max_entries")]
+    pub cache_max_entries: usize,
+    // Our in-game name, highlighted even in replays recorded by someone else
+    #[serde(default)]
+    pub own_player_name: Option<String>,
+    // Player names to highlight, compared case-insensitively
+    #[serde(default)]
+    pub friends: Vec<String>,
+    // Tags offered in the player note editor
+    #[serde(default = "default_note_tags")]
+    pub note_tags: Vec<String>,
+    #[serde(default)]
+    pub team1_sort: TeamSortKey,
+    #[serde(default)]
+    pub team2_sort: TeamSortKey,
+    // Lifetime or recent numbers on the player cards
+    #[serde(default)]
+    pub stats_window: StatsWindow,
+    // Kept last, TOML tables have to follow the plain values
+    #[serde(default)]
+    pub win_model: WinModel,
+}
+
+fn default_note_tags() -> Vec<String> {
+    ["toxic", "thrower", "good CV", "carries", "AFK"]
+        .map(String::from)
+        .to_vec()
+}
+
+fn default_requests_per_second() -> u32 {
+    10
+}
+
+fn default_cache_ttl_hours() -> u64 {
+    24
+}
+
+fn default_cache_max_entries() -> usize {
+    5000
 }
 
 impl Default for Config {
@@ -16,6 +83,22 @@ impl Default for Config {
             window_width: 1200,
             window_height: 800,
             selected_folder: None,
+            region: Region::default(),
+            profile_site: ProfileSite::default(),
+            custom_profile_url: String::new(),
+            custom_clan_url: String::new(),
+            application_id: String::new(),
+            api_base_url: None,
+            requests_per_second: default_requests_per_second(),
+            cache_ttl_hours: default_cache_ttl_hours(),
+            cache_max_entries: default_cache_max_entries(),
+            own_player_name: None,
+            friends: Vec::new(),
+            note_tags: default_note_tags(),
+            team1_sort: TeamSortKey::default(),
+            team2_sort: TeamSortKey::default(),
+            stats_window: StatsWindow::default(),
+            win_model: WinModel::default(),
         }
     }
 }
@@ -61,9 +144,15 @@ impl Config {
         }
     }
 
-    fn get_config_path() -> PathBuf {
+    // Directory holding the config file, logs and other app data
+    pub fn data_dir() -> PathBuf {
         let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
         path.push("okay-you-very-pro");
+        path
+    }
+
+    fn get_config_path() -> PathBuf {
+        let mut path = Self::data_dir();
         path.push("config.toml");
         info!("Config path: {:?}", path);
         path
@@ -75,4 +164,10 @@ impl Config {
             .map(|f| std::path::Path::new(f).join("replays"))
             .unwrap_or_default()
     }
+
+    pub fn arena_info_path(&self) -> Option<PathBuf> {
+        self.selected_folder
+            .as_ref()
+            .map(|_| self.replay_path().join(crate::arena::ARENA_INFO_FILE))
+    }
 }
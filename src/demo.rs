@@ -0,0 +1,586 @@
+use crate::ships::{ShipClass, ShipDatabase, ShipInfo};
+use crate::stats::BattleStats;
+use crate::{AccountNumbers, CardKind, Player, ShipNumbers, StatsStatus};
+
+// Sample teams shown when the viewer is started with `--demo`
+pub fn sample_teams() -> (Vec<Player>, Vec<Player>) {
+    let team1 = vec![
+        Player {
+            name: "Alpha".to_string(),
+            account_id: 1001,
+            ship_name: "Ship1".to_string(),
+            ship_id: 1,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2754,
+                winrate: 49.96,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 156,
+                winrate: 48.5,
+                pr: Some(856),
+                avg_damage: 84849.0,
+                frags: 0.8,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Own,
+        },
+        Player {
+            name: "Beta".to_string(),
+            account_id: 1002,
+            ship_name: "Ship2".to_string(),
+            ship_id: 2,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 4852,
+                winrate: 47.22,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 342,
+                winrate: 51.2,
+                pr: Some(1425),
+                avg_damage: 132932.0,
+                frags: 1.2,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::DivisionMate,
+        },
+        Player {
+            name: "Charlie".to_string(),
+            account_id: 1003,
+            ship_name: "Ship3".to_string(),
+            ship_id: 3,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 1991,
+                winrate: 43.45,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 89,
+                winrate: 46.8,
+                pr: Some(485),
+                avg_damage: 57493.0,
+                frags: 0.6,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Delta".to_string(),
+            account_id: 1004,
+            ship_name: "Ship4".to_string(),
+            ship_id: 4,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 844,
+                winrate: 45.02,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 234,
+                winrate: 44.9,
+                pr: Some(892),
+                avg_damage: 71441.0,
+                frags: 0.7,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Echo".to_string(),
+            account_id: 1005,
+            ship_name: "Ship5".to_string(),
+            ship_id: 5,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 5110,
+                winrate: 48.53,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 445,
+                winrate: 52.1,
+                pr: Some(447),
+                avg_damage: 45591.0,
+                frags: 0.9,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Friend,
+        },
+        Player {
+            name: "Foxtrot".to_string(),
+            account_id: 1006,
+            ship_name: "Ship6".to_string(),
+            ship_id: 6,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 9349,
+                winrate: 47.3,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 678,
+                winrate: 49.9,
+                pr: Some(1248),
+                avg_damage: 51313.0,
+                frags: 1.1,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Mike".to_string(),
+            account_id: 1007,
+            ship_name: "Ship13".to_string(),
+            ship_id: 13,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 3245,
+                winrate: 51.23,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 234,
+                winrate: 52.8,
+                pr: Some(1256),
+                avg_damage: 98765.0,
+                frags: 1.3,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "November".to_string(),
+            account_id: 1008,
+            ship_name: "Ship14".to_string(),
+            ship_id: 14,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 4123,
+                winrate: 48.76,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 345,
+                winrate: 47.5,
+                pr: Some(892),
+                avg_damage: 65432.0,
+                frags: 0.8,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Oscar".to_string(),
+            account_id: 1009,
+            ship_name: "Ship15".to_string(),
+            ship_id: 15,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2876,
+                winrate: 52.34,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 456,
+                winrate: 53.2,
+                pr: Some(1456),
+                avg_damage: 112345.0,
+                frags: 1.4,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Papa".to_string(),
+            account_id: 1010,
+            ship_name: "Ship16".to_string(),
+            ship_id: 16,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 5678,
+                winrate: 46.78,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 567,
+                winrate: 45.9,
+                pr: Some(678),
+                avg_damage: 45678.0,
+                frags: 0.7,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Quebec".to_string(),
+            account_id: 1011,
+            ship_name: "Ship17".to_string(),
+            ship_id: 17,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 3456,
+                winrate: 50.12,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 678,
+                winrate: 51.5,
+                pr: Some(1234),
+                avg_damage: 87654.0,
+                frags: 1.0,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Romeo".to_string(),
+            account_id: 1012,
+            ship_name: "Ship18".to_string(),
+            ship_id: 18,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 4321,
+                winrate: 49.87,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 789,
+                winrate: 48.7,
+                pr: Some(987),
+                avg_damage: 76543.0,
+                frags: 0.9,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+    ];
+
+    let team2 = vec![
+        Player {
+            name: "Golf".to_string(),
+            account_id: 1013,
+            ship_name: "Ship7".to_string(),
+            ship_id: 7,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2644,
+                winrate: 49.92,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 223,
+                winrate: 53.4,
+                pr: Some(1350),
+                avg_damage: 103170.0,
+                frags: 1.2,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Hotel".to_string(),
+            account_id: 1014,
+            ship_name: "Ship8".to_string(),
+            ship_id: 8,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2623,
+                winrate: 49.49,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 167,
+                winrate: 47.8,
+                pr: Some(1121),
+                avg_damage: 139917.0,
+                frags: 1.1,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "India".to_string(),
+            account_id: 1015,
+            ship_name: "Ship9".to_string(),
+            ship_id: 9,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2280,
+                winrate: 49.74,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 445,
+                winrate: 50.2,
+                pr: Some(1236),
+                avg_damage: 105548.0,
+                frags: 1.0,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Juliet".to_string(),
+            account_id: 1016,
+            ship_name: "Ship10".to_string(),
+            ship_id: 10,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2923,
+                winrate: 47.21,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 332,
+                winrate: 46.9,
+                pr: Some(892),
+                avg_damage: 92047.0,
+                frags: 0.8,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Kilo".to_string(),
+            account_id: 1017,
+            ship_name: "Ship11".to_string(),
+            ship_id: 11,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 3509,
+                winrate: 53.43,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 221,
+                winrate: 55.6,
+                pr: Some(962),
+                avg_damage: 66757.0,
+                frags: 1.3,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Lima".to_string(),
+            account_id: 1018,
+            ship_name: "Ship12".to_string(),
+            ship_id: 12,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 4419,
+                winrate: 51.96,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 554,
+                winrate: 50.8,
+                pr: Some(1009),
+                avg_damage: 119177.0,
+                frags: 1.4,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Sierra".to_string(),
+            account_id: 1019,
+            ship_name: "Ship19".to_string(),
+            ship_id: 19,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 3789,
+                winrate: 48.45,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 456,
+                winrate: 49.2,
+                pr: Some(876),
+                avg_damage: 67890.0,
+                frags: 0.9,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Tango".to_string(),
+            account_id: 1020,
+            ship_name: "Ship20".to_string(),
+            ship_id: 20,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2987,
+                winrate: 52.67,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 567,
+                winrate: 54.1,
+                pr: Some(1345),
+                avg_damage: 98765.0,
+                frags: 1.5,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Uniform".to_string(),
+            account_id: 1021,
+            ship_name: "Ship21".to_string(),
+            ship_id: 21,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 4567,
+                winrate: 47.89,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 678,
+                winrate: 46.8,
+                pr: Some(765),
+                avg_damage: 54321.0,
+                frags: 0.7,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Victor".to_string(),
+            account_id: 1022,
+            ship_name: "Ship22".to_string(),
+            ship_id: 22,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 3456,
+                winrate: 50.34,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 789,
+                winrate: 51.7,
+                pr: Some(1123),
+                avg_damage: 87654.0,
+                frags: 1.1,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Whiskey".to_string(),
+            account_id: 1023,
+            ship_name: "Ship23".to_string(),
+            ship_id: 23,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 4321,
+                winrate: 49.56,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 890,
+                winrate: 48.9,
+                pr: Some(987),
+                avg_damage: 76543.0,
+                frags: 0.8,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+        Player {
+            name: "Xray".to_string(),
+            account_id: 1024,
+            ship_name: "Ship24".to_string(),
+            ship_id: 24,
+            ship_info: None,
+            clan: None,
+            account: Some(AccountNumbers {
+                battles: 2987,
+                winrate: 51.78,
+            }),
+            ship: Some(ShipNumbers {
+                battles: 567,
+                winrate: 52.5,
+                pr: Some(1234),
+                avg_damage: 98765.0,
+                frags: 1.2,
+            }),
+            recent: Vec::new(),
+            status: StatsStatus::Loaded,
+            card: CardKind::Normal,
+        },
+    ];
+
+    (team1, team2)
+}
+
+// Ship database for the sample ids, mostly high tier like a real lobby
+pub fn sample_ships() -> ShipDatabase {
+    const CLASSES: [ShipClass; 5] = [
+        ShipClass::Destroyer,
+        ShipClass::Cruiser,
+        ShipClass::Battleship,
+        ShipClass::Cruiser,
+        ShipClass::AirCarrier,
+    ];
+    (1..=24)
+        .map(|ship_id: u64| {
+            let info = ShipInfo {
+                name: format!("Ship{}", ship_id),
+                class: CLASSES[ship_id as usize % CLASSES.len()],
+                tier: 8 + (ship_id % 3) as u8,
+                nation: "usa".to_string(),
+                premium: ship_id.is_multiple_of(4),
+                special: false,
+            };
+            (ship_id, info)
+        })
+        .collect()
+}
+
+// Per-ship stats for the detail pane, varied a little per account
+pub fn sample_ship_stats(account_id: i64) -> Vec<(u64, BattleStats)> {
+    (1..=24)
+        .map(|ship_id: u64| {
+            let seed = (account_id as u64)
+                .wrapping_mul(31)
+                .wrapping_add(ship_id * 17)
+                % 100;
+            let battles = 20 + seed as u32 * 3;
+            let stats = BattleStats {
+                battles,
+                wins: battles * (44 + seed as u32 % 16) / 100,
+                damage_dealt: battles as u64 * (40_000 + seed * 900),
+                frags: battles * (5 + seed as u32 % 10) / 10,
+            };
+            (ship_id, stats)
+        })
+        .collect()
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::region::Region;
+use crate::replay::Outcome;
+
+pub const ENCOUNTERS_FILE: &str = "encounters.json";
+
+// Older meetings only count towards the total
+const MAX_MEETINGS_PER_PLAYER: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Meeting {
+    // Arena date and time as written by the game
+    pub date_time: String,
+    pub ally: bool,
+    // Our result in that battle, once its replay was found
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Encounters {
+    pub name: String,
+    pub count: u32,
+    // Most recent last
+    pub meetings: Vec<Meeting>,
+}
+
+impl Encounters {
+    // Tooltip text for the "seen before" badge
+    pub fn describe(&self) -> String {
+        let mut lines = vec![format!("Met {} times", self.count)];
+        if let Some(last) = self.meetings.last() {
+            lines.push(format!("Last seen {}", last.date_time));
+        }
+        for (ally, label) in [(true, "ally"), (false, "enemy")] {
+            let meetings: Vec<&Meeting> = self
+                .meetings
+                .iter()
+                .filter(|meeting| meeting.ally == ally)
+                .collect();
+            if meetings.is_empty() {
+                continue;
+            }
+            let count = |outcome| {
+                meetings
+                    .iter()
+                    .filter(|meeting| meeting.outcome == outcome)
+                    .count()
+            };
+            lines.push(format!(
+                "As {}: {}W {}L {}D {}?",
+                label,
+                count(Some(Outcome::Win)),
+                count(Some(Outcome::Loss)),
+                count(Some(Outcome::Draw)),
+                count(None),
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+// Every player the viewer has shown, keyed by region and account id
+pub struct EncounterLog {
+    path: PathBuf,
+    players: HashMap<String, Encounters>,
+}
+
+impl EncounterLog {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(ENCOUNTERS_FILE)
+    }
+
+    pub fn load(path: &Path) -> Self {
+        info!("Loading encounter log from: {:?}", path);
+
+        let players = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("Error parsing encounter log: {}", e);
+                HashMap::new()
+            }),
+            Err(e) => {
+                warn!("No encounter log found: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            players,
+        }
+    }
+
+    pub fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&self.players) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(&self.path, contents) {
+                    error!("Error saving encounter log: {}", e);
+                }
+            }
+            Err(e) => error!("Error serializing encounter log: {}", e),
+        }
+    }
+
+    fn key(region: Region, account_id: i64) -> String {
+        format!("{}:{}", region.code(), account_id)
+    }
+
+    // Loading the same battle twice does not count as a second meeting
+    pub fn log(
+        &mut self,
+        region: Region,
+        account_id: i64,
+        name: &str,
+        date_time: &str,
+        ally: bool,
+    ) {
+        let encounters = self
+            .players
+            .entry(Self::key(region, account_id))
+            .or_default();
+        encounters.name = name.to_string();
+        if encounters
+            .meetings
+            .iter()
+            .any(|meeting| meeting.date_time == date_time)
+        {
+            return;
+        }
+        encounters.count += 1;
+        encounters.meetings.push(Meeting {
+            date_time: date_time.to_string(),
+            ally,
+            outcome: None,
+        });
+        if encounters.meetings.len() > MAX_MEETINGS_PER_PLAYER {
+            encounters.meetings.remove(0);
+        }
+    }
+
+    // Meetings before the battle at `current_date_time`, None for strangers
+    pub fn previous(
+        &self,
+        region: Region,
+        account_id: i64,
+        current_date_time: &str,
+    ) -> Option<Encounters> {
+        let encounters = self.players.get(&Self::key(region, account_id))?;
+        let meetings: Vec<Meeting> = encounters
+            .meetings
+            .iter()
+            .filter(|meeting| meeting.date_time != current_date_time)
+            .cloned()
+            .collect();
+        let current = encounters.meetings.len() - meetings.len();
+        let count = encounters.count - current as u32;
+        (count > 0).then(|| Encounters {
+            name: encounters.name.clone(),
+            count,
+            meetings,
+        })
+    }
+
+    pub fn set_outcomes(&mut self, outcomes: &[(String, Outcome)]) {
+        for encounters in self.players.values_mut() {
+            for meeting in &mut encounters.meetings {
+                if let Some((_, outcome)) = outcomes
+                    .iter()
+                    .find(|(date_time, _)| *date_time == meeting.date_time)
+                {
+                    meeting.outcome = Some(*outcome);
+                }
+            }
+        }
+        self.save();
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::replay::{Outcome, Replay, ReplayHeader, REPLAY_EXTENSION};
+use crate::{AccountNumbers, Player, ShipNumbers};
+
+// One JSON record per line, so recording a battle only appends
+pub const HISTORY_FILE: &str = "battle_history.jsonl";
+// Battles whose replay has not shown up by then (e.g. left early) stop being looked for
+const OUTCOME_WAIT_SECS: u64 = 24 * 60 * 60;
+
+// A player's stats as they were shown when the battle was recorded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub name: String,
+    pub account_id: i64,
+    pub ship_id: u64,
+    pub ship_name: String,
+    pub account: Option<AccountNumbers>,
+    pub ship: Option<ShipNumbers>,
+}
+
+impl From<&Player> for PlayerSnapshot {
+    fn from(player: &Player) -> Self {
+        Self {
+            name: player.name.clone(),
+            account_id: player.account_id,
+            ship_id: player.ship_id,
+            ship_name: player.ship_name.clone(),
+            account: player.account,
+            ship: player.ship,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BattleRecord {
+    // As written by the game, also used to find the battle's replay
+    pub date_time: String,
+    pub map: String,
+    pub mode: String,
+    pub player_vehicle: String,
+    // Allies including ourselves, then enemies
+    pub team1: Vec<PlayerSnapshot>,
+    pub team2: Vec<PlayerSnapshot>,
+    // Strength of team1 minus team2 at battle start, see `prediction`
+    pub strength_difference: Option<f32>,
+    // Filled in once the replay shows up
+    #[serde(default)]
+    pub outcome: Option<Outcome>,
+    // Unix time the battle was recorded, older records predate it and count as expired
+    #[serde(default)]
+    pub recorded_at: u64,
+}
+
+pub struct BattleHistory {
+    path: PathBuf,
+    records: Vec<BattleRecord>,
+}
+
+impl BattleHistory {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(HISTORY_FILE)
+    }
+
+    // Broken lines are skipped so one bad write does not lose the whole history
+    pub fn load(path: &Path) -> Self {
+        info!("Loading battle history from: {:?}", path);
+
+        let records: Vec<BattleRecord> = match fs::read_to_string(path) {
+            Ok(contents) => contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| {
+                    serde_json::from_str(line)
+                        .map_err(|e| warn!("Skipping broken history line: {}", e))
+                        .ok()
+                })
+                .collect(),
+            Err(e) => {
+                warn!("No battle history found: {}", e);
+                Vec::new()
+            }
+        };
+        info!("Loaded {} recorded battles", records.len());
+
+        Self {
+            path: path.to_path_buf(),
+            records,
+        }
+    }
+
+    // A battle seen again (e.g. after restarting the viewer) replaces its old record
+    pub fn record(&mut self, record: BattleRecord) {
+        info!("Recording battle on {} at {}", record.map, record.date_time);
+        match self
+            .records
+            .iter_mut()
+            .find(|existing| existing.date_time == record.date_time)
+        {
+            Some(existing) => {
+                *existing = record;
+                self.save();
+            }
+            None => {
+                self.append(&record);
+                self.records.push(record);
+            }
+        }
+    }
+
+    // Battles still waiting for their replay
+    pub fn pending_outcomes(&self, now: u64) -> HashSet<String> {
+        self.records
+            .iter()
+            .filter(|record| record.outcome.is_none())
+            .filter(|record| record.recorded_at + OUTCOME_WAIT_SECS > now)
+            .map(|record| record.date_time.clone())
+            .collect()
+    }
+
+    pub fn set_outcomes(&mut self, outcomes: &[(String, Outcome)]) {
+        for (date_time, outcome) in outcomes {
+            for record in self
+                .records
+                .iter_mut()
+                .filter(|record| &record.date_time == date_time)
+            {
+                record.outcome = Some(*outcome);
+            }
+        }
+        self.save();
+    }
+
+    // Account winrate every time we met the player, oldest first
+    pub fn account_winrates(&self, account_id: i64) -> Vec<f32> {
+        self.records
+            .iter()
+            .flat_map(|record| record.team1.iter().chain(&record.team2))
+            .filter(|snapshot| snapshot.account_id == account_id)
+            .filter_map(|snapshot| Some(snapshot.account?.winrate))
+            .collect()
+    }
+
+    // Strength difference and whether team1 won, draws tell us nothing
+    pub fn calibration_samples(&self) -> Vec<(f32, bool)> {
+        self.records
+            .iter()
+            .filter_map(
+                |record| match (record.strength_difference, record.outcome) {
+                    (Some(difference), Some(Outcome::Win)) => Some((difference, true)),
+                    (Some(difference), Some(Outcome::Loss)) => Some((difference, false)),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    fn append(&self, record: &BattleRecord) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let line = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Error serializing battle record: {}", e);
+                return;
+            }
+        };
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = result {
+            error!("Error saving battle history: {}", e);
+        }
+    }
+
+    fn save(&self) {
+        let mut contents = String::new();
+        for record in &self.records {
+            match serde_json::to_string(record) {
+                Ok(line) => {
+                    contents.push_str(&line);
+                    contents.push('\n');
+                }
+                Err(e) => error!("Error serializing battle record: {}", e),
+            }
+        }
+        if let Err(e) = fs::write(&self.path, contents) {
+            error!("Error saving battle history: {}", e);
+        }
+    }
+}
+
+// Replay files are named "yyyymmdd_hhmmss_<ship>_<map>" after the battle start. The
+// seconds are left out as they can differ from the arena's date_time.
+fn file_name_prefix(date_time: &str) -> Option<String> {
+    let (date, time) = date_time.split_once(' ')?;
+    let mut date = date.split('.');
+    let (day, month, year) = (date.next()?, date.next()?, date.next()?);
+    let mut time = time.split(':');
+    let (hour, minute) = (time.next()?, time.next()?);
+    Some(format!("{}{}{}_{}{}", year, month, day, hour, minute))
+}
+
+// Read the outcome of every pending battle whose replay is in the folder
+pub fn find_outcomes(replay_path: &Path, pending: &HashSet<String>) -> Vec<(String, Outcome)> {
+    let prefixes: Vec<String> = pending
+        .iter()
+        .filter_map(|date_time| file_name_prefix(date_time))
+        .collect();
+    if prefixes.is_empty() {
+        return Vec::new();
+    }
+    let entries = match fs::read_dir(replay_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read replay folder: {}", e);
+            return Vec::new();
+        }
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+        })
+        // Only pending battles are worth decrypting the whole replay for
+        .filter(|path| {
+            ReplayHeader::read(path).is_ok_and(|header| pending.contains(&header.arena.date_time))
+        })
+        .filter_map(|path| Replay::open(&path).ok())
+        .filter_map(|replay| {
+            let outcome = replay.results()?.outcome;
+            Some((replay.header.arena.date_time, outcome))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replay_file_names_start_with_the_battle_minute() {
+        assert_eq!(
+            file_name_prefix("14.06.2024 20:31:05").as_deref(),
+            Some("20240614_2031")
+        );
+        assert_eq!(file_name_prefix("14.06.2024"), None);
+    }
+
+    #[test]
+    fn battles_stop_waiting_for_their_replay_after_a_day() {
+        let record = |date_time: &str, recorded_at| BattleRecord {
+            date_time: date_time.to_string(),
+            map: String::new(),
+            mode: String::new(),
+            player_vehicle: String::new(),
+            team1: Vec::new(),
+            team2: Vec::new(),
+            strength_difference: None,
+            outcome: None,
+            recorded_at,
+        };
+        let history = BattleHistory {
+            path: PathBuf::new(),
+            records: vec![
+                record("14.06.2024 20:31:05", 1_000),
+                record("15.06.2024 20:31:05", 1_000 + OUTCOME_WAIT_SECS),
+            ],
+        };
+        let pending = history.pending_outcomes(1_000 + OUTCOME_WAIT_SECS + 1);
+        assert_eq!(pending.len(), 1);
+        assert!(pending.contains("15.06.2024 20:31:05"));
+    }
+}
@@ -1,27 +1,77 @@
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 use iced::application::StyleSheet;
 use iced::font::Weight;
+use iced::overlay::menu;
 use iced::theme::{self, Container as ThemeContainer, Text as TextTheme};
 use iced::widget::container::Appearance;
 use iced::widget::{
-    button, column, container, image as iced_image, row, scrollable, svg, text, Button, Container,
-    Text,
+    button, canvas, column, container, image as iced_image, pick_list, row, scrollable, svg, text,
+    text_input, tooltip, Button, Container, Row, Text,
 };
 use iced::Color;
 use iced::{Application, Command, Element, Length, Settings, Theme};
 use image as image_rs;
 use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
 use tracing::level_filters::LevelFilter;
 use tracing::{debug, error, info, warn};
 
+mod arena;
+mod cache;
+mod charts;
 mod colors;
 mod config;
+mod demo;
+mod encounters;
+mod history;
+mod modal;
 mod my_text;
+mod notes;
+mod player_details;
+mod pr;
+mod prediction;
+mod profile;
+mod rating;
+mod region;
+mod replay;
+mod replay_browser;
+mod sessions;
+mod ships;
+mod snapshots;
+mod stats;
+mod team_sort;
+mod team_summary;
+mod watcher;
+mod worker;
+use arena::{ArenaInfo, ArenaVehicle, Relation};
+use cache::{CachedStats, StatsCache};
 use config::Config;
+use encounters::EncounterLog;
+use history::{BattleHistory, BattleRecord, PlayerSnapshot};
+use modal::Modal;
 use my_text::*;
+use notes::{NotesStore, PlayerNote};
+use player_details::PlayerDetails;
+use pr::ExpectedValues;
+use prediction::WinModel;
+use rating::Rating;
+use region::Region;
+use replay::{Outcome, ReplayHeader};
+use replay_browser::{ReplaySortKey, ReplaySummary};
+use sessions::{SessionBattle, SessionStore};
+use ships::{ShipClass, ShipDatabase, ShipInfo};
+use snapshots::{RecentStats, SnapshotStore, StatsWindow};
+use stats::{
+    BattleStats, ClanInfo, PlayerStats, RateLimited, StatsError, StatsProvider, WargamingApi,
+};
+use team_sort::{Team, TeamSortKey};
+use team_summary::{ClassSummary, TeamSummary};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
+use watcher::ArenaEvent;
 
 use crate::colors::*;
 
@@ -29,22 +79,1040 @@ use crate::colors::*;
 #[derive(Debug, Clone)]
 struct Player {
     name: String,
+    // 0 until the stats lookup has resolved the name on the selected region
+    account_id: i64,
+    ship_name: String,
+    ship_id: u64,
+    // None when the ship is missing from the ship database
+    ship_info: Option<ShipInfo>,
+    clan: Option<ClanInfo>,
+    // None when the account has no random battles
+    account: Option<AccountNumbers>,
+    // None when the player has never taken this ship into a random battle
+    ship: Option<ShipNumbers>,
+    // Same numbers for the windows we have older snapshots of
+    recent: Vec<(StatsWindow, Option<AccountNumbers>, Option<ShipNumbers>)>,
+    status: StatsStatus,
+    card: CardKind,
+}
+
+#[cfg(test)]
+impl Player {
+    // Player with (ship battles, winrate, ship PR) in a ship of `class`, the account
+    // has ten times the ship's battles at the same winrate. No stats means hidden.
+    fn sample(class: ShipClass, stats: Option<(u32, f32, u32)>) -> Self {
+        Self {
+            name: "Player".to_string(),
+            account_id: 1,
+            ship_name: "Ship".to_string(),
+            ship_id: 1,
+            ship_info: Some(ShipInfo {
+                name: "Ship".to_string(),
+                class,
+                tier: 10,
+                nation: "usa".to_string(),
+                premium: false,
+                special: false,
+            }),
+            clan: None,
+            account: stats.map(|(battles, winrate, _)| AccountNumbers {
+                battles: battles * 10,
+                winrate,
+            }),
+            ship: stats.map(|(battles, winrate, pr)| ShipNumbers {
+                battles,
+                winrate,
+                pr: Some(pr),
+                avg_damage: 0.0,
+                frags: 0.0,
+            }),
+            recent: Vec::new(),
+            status: match stats {
+                Some(_) => StatsStatus::Loaded,
+                None => StatsStatus::Hidden,
+            },
+            card: CardKind::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct AccountNumbers {
+    battles: u32,
     winrate: f32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct ShipNumbers {
     battles: u32,
-    ship_name: String,
-    ship_winrate: f32,
-    ship_battles: u32,
-    pr: u32,
+    winrate: f32,
+    // None when there are no expected values for the ship
+    pr: Option<u32>,
     avg_damage: f32,
     frags: f32,
 }
 
+// How the player's card stands out, most specific first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardKind {
+    Own,
+    DivisionMate,
+    Friend,
+    // From our notes, so only known once the account id is resolved
+    Blacklisted,
+    Normal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StatsStatus {
+    Loading,
+    Loaded,
+    // Shown from an expired cache entry because the lookup failed
+    Stale,
+    Hidden,
+    // No account with this name on the selected region, e.g. renamed since
+    NotFound,
+    Bot,
+    Failed(String),
+}
+
+impl Player {
+    // Stats are filled in later, the arena file only tells us who is in the battle
+    fn from_arena_vehicle(vehicle: &ArenaVehicle, own_division: Option<i64>) -> Self {
+        let card = if vehicle.relation() == Relation::Own {
+            CardKind::Own
+        } else if own_division.is_some() && vehicle.division() == own_division {
+            CardKind::DivisionMate
+        } else {
+            CardKind::Normal
+        };
+        Self {
+            name: vehicle.name.clone(),
+            account_id: 0,
+            ship_name: vehicle.ship_id.to_string(),
+            ship_id: vehicle.ship_id,
+            ship_info: None,
+            clan: None,
+            account: None,
+            ship: None,
+            recent: Vec::new(),
+            status: if vehicle.is_bot() {
+                StatsStatus::Bot
+            } else {
+                StatsStatus::Loading
+            },
+            card,
+        }
+    }
+
+    // The arena only knows about ourselves and our division, the rest comes from the config
+    fn apply_card_kind(&mut self, config: &Config) {
+        if config
+            .own_player_name
+            .as_deref()
+            .is_some_and(|own| own.eq_ignore_ascii_case(&self.name))
+        {
+            self.card = CardKind::Own;
+        } else if self.card == CardKind::Normal
+            && config
+                .friends
+                .iter()
+                .any(|friend| friend.eq_ignore_ascii_case(&self.name))
+        {
+            self.card = CardKind::Friend;
+        }
+    }
+
+    fn apply_ship_info(&mut self, ships: &ShipDatabase) {
+        self.ship_info = ships.get(self.ship_id).cloned();
+        if let Some(info) = &self.ship_info {
+            self.ship_name = info.name.clone();
+        }
+    }
+
+    fn apply_stats(
+        &mut self,
+        stats: &PlayerStats,
+        recent: &[(StatsWindow, RecentStats)],
+        expected_values: &ExpectedValues,
+    ) {
+        self.account_id = stats.account_id;
+        self.clan = stats.clan.clone();
+        self.account = account_numbers(&stats.account);
+        self.ship = ship_numbers(self.ship_id, &stats.ship, expected_values);
+        self.recent = recent
+            .iter()
+            .map(|(window, recent)| {
+                (
+                    *window,
+                    account_numbers(&recent.account),
+                    recent
+                        .ship
+                        .and_then(|ship| ship_numbers(self.ship_id, &ship, expected_values)),
+                )
+            })
+            .collect();
+    }
+
+    // Stats and account ids from another region don't carry over, the name is resolved again
+    fn clear_stats(&mut self) {
+        self.account_id = 0;
+        self.clan = None;
+        self.account = None;
+        self.ship = None;
+        self.recent.clear();
+    }
+
+    // Numbers for the card, a recent window without snapshots shows nothing
+    fn numbers(&self, window: StatsWindow) -> (Option<AccountNumbers>, Option<ShipNumbers>) {
+        if window == StatsWindow::Lifetime {
+            return (self.account, self.ship);
+        }
+        self.recent
+            .iter()
+            .find(|(recent_window, _, _)| *recent_window == window)
+            .map(|(_, account, ship)| (*account, *ship))
+            .unwrap_or((None, None))
+    }
+
+    // Why a recent window has no numbers to show, None when it has some
+    fn recent_placeholder(&self, window: StatsWindow) -> Option<&'static str> {
+        if window == StatsWindow::Lifetime {
+            return None;
+        }
+        match self
+            .recent
+            .iter()
+            .find(|(recent_window, _, _)| *recent_window == window)
+        {
+            None => Some("No snapshot yet, recent stats start with tomorrow's lookup"),
+            Some((_, None, _)) => Some("No battles in this window"),
+            Some(_) => None,
+        }
+    }
+}
+
+fn account_numbers(stats: &BattleStats) -> Option<AccountNumbers> {
+    (stats.battles > 0).then(|| AccountNumbers {
+        battles: stats.battles,
+        winrate: stats.winrate(),
+    })
+}
+
+fn ship_numbers(
+    ship_id: u64,
+    stats: &BattleStats,
+    expected_values: &ExpectedValues,
+) -> Option<ShipNumbers> {
+    (stats.battles > 0).then(|| ShipNumbers {
+        battles: stats.battles,
+        winrate: stats.winrate(),
+        pr: expected_values
+            .ship_pr(ship_id, stats)
+            .map(|pr| pr.round() as u32),
+        avg_damage: stats.avg_damage(),
+        frags: stats.avg_frags(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Teams,
+    ReplayBrowser,
+    Session,
+}
+
+// Entry of the session picker, `index` points into `SessionStore::sessions`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SessionChoice {
+    index: usize,
+    label: String,
+}
+
+impl std::fmt::Display for SessionChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
 // Main application state
 struct StatsViewer {
     team1: Vec<Player>,
     team2: Vec<Player>,
     config: Config,
     last_folder_path: Option<String>,
+    demo: bool,
+    view: View,
+    replays: Vec<ReplaySummary>,
+    // Replays whose duration is being read in the background
+    duration_scan: Vec<PathBuf>,
+    replay_sort: ReplaySortKey,
+    replay_sort_descending: bool,
+    stats_provider: Arc<dyn StatsProvider>,
+    stats_cache: Arc<Mutex<StatsCache>>,
+    stats_snapshots: Arc<Mutex<SnapshotStore>>,
+    expected_values: ExpectedValues,
+    ships: ShipDatabase,
+    downloading_ships: bool,
+    // Live battle from tempArenaInfo.json, None when showing a replay or nothing
+    arena: Option<ArenaInfo>,
+    history: BattleHistory,
+    encounters: EncounterLog,
+    notes: NotesStore,
+    // Account id and unsaved copy of the note being edited
+    note_draft: Option<(i64, PlayerNote)>,
+    // Replaces the team view while open
+    player_details: Option<PlayerDetails>,
+    sessions: SessionStore,
+    // Session shown in the session view, None for the running one
+    selected_session: Option<usize>,
+}
+
+impl StatsViewer {
+    fn load_arena(&mut self) -> Command<Message> {
+        self.arena = self
+            .config
+            .arena_info_path()
+            .and_then(|path| ArenaInfo::load(&path));
+        let teams = match &self.arena {
+            Some(arena) => Self::teams_from_arena(arena),
+            None => (Vec::new(), Vec::new()),
+        };
+        Command::batch([self.set_teams(teams), self.download_ships()])
+    }
+
+    // Needs resolved account ids, so this runs once the stats lookups are done
+    fn log_encounters(&mut self) {
+        let Some(arena) = &self.arena else {
+            return;
+        };
+        let allies = self.team1.iter().map(|player| (player, true));
+        let enemies = self.team2.iter().map(|player| (player, false));
+        for (player, ally) in allies.chain(enemies) {
+            if player.status == StatsStatus::Bot
+                || player.account_id <= 0
+                || player.name == arena.player_name
+            {
+                continue;
+            }
+            self.encounters.log(
+                self.config.region,
+                player.account_id,
+                &player.name,
+                &arena.date_time,
+                ally,
+            );
+        }
+        self.encounters.save();
+    }
+
+    // Store the live battle with the stats shown on the cards
+    fn record_battle(&mut self) {
+        let Some(arena) = &self.arena else {
+            return;
+        };
+        let snapshot = |players: &[Player]| players.iter().map(PlayerSnapshot::from).collect();
+        let strength_difference = prediction::team_strength(&self.team1)
+            .zip(prediction::team_strength(&self.team2))
+            .map(|(strength1, strength2)| strength1 - strength2);
+
+        self.history.record(BattleRecord {
+            date_time: arena.date_time.clone(),
+            map: arena.map_display_name.clone(),
+            mode: arena.match_group.clone(),
+            player_vehicle: arena.player_vehicle.clone(),
+            team1: snapshot(&self.team1),
+            team2: snapshot(&self.team2),
+            strength_difference,
+            outcome: None,
+            recorded_at: cache::now_secs(),
+        });
+    }
+
+    // Look for the replays of battles we have no result for yet
+    fn find_outcomes(&self) -> Command<Message> {
+        let pending = self.history.pending_outcomes(cache::now_secs());
+        if pending.is_empty() || self.config.selected_folder.is_none() {
+            return Command::none();
+        }
+        let replay_path = self.config.replay_path();
+        Command::perform(
+            worker::run(move || history::find_outcomes(&replay_path, &pending)),
+            Message::OutcomesFound,
+        )
+    }
+
+    // Pick up our replays written since the session started
+    fn scan_session(&self) -> Command<Message> {
+        if self.demo || self.config.selected_folder.is_none() {
+            return Command::none();
+        }
+        let session = self.sessions.current();
+        let since = session.started_at;
+        let known = session
+            .battles
+            .iter()
+            .map(|battle| battle.date_time.clone())
+            .collect();
+        let replay_path = self.config.replay_path();
+        Command::perform(
+            worker::run(move || sessions::scan_new_battles(&replay_path, since, &known)),
+            Message::SessionBattlesFound,
+        )
+    }
+
+    // Our lifetime PR at the start of the session, once we know who we are
+    fn fetch_session_baseline(&self) -> Command<Message> {
+        let session = self.sessions.current();
+        let Some(name) = session.own_player_name() else {
+            return Command::none();
+        };
+        if session.baseline_pr.is_some() || self.config.application_id.is_empty() {
+            return Command::none();
+        }
+        let name = name.to_string();
+        let provider = Arc::clone(&self.stats_provider);
+        let region = self.config.region;
+        Command::perform(
+            worker::run(move || {
+                let account_id = provider.find_account_id(region, &name)?;
+                provider.all_ship_stats(region, account_id)
+            }),
+            Message::SessionBaselineLoaded,
+        )
+    }
+
+    // Refit the win chance model whenever we learn about new results
+    fn calibrate_win_model(&mut self) {
+        let samples = self.history.calibration_samples();
+        let Some(model) = WinModel::calibrate(&samples) else {
+            return;
+        };
+        info!(
+            "Calibrated win model on {} battles: {:?}",
+            samples.len(),
+            model
+        );
+        let mut config = Config::load();
+        config.win_model = model;
+        config.save();
+        self.config = config;
+    }
+
+    // Replace both teams and start looking up everyone's stats
+    fn set_teams(&mut self, (team1, team2): (Vec<Player>, Vec<Player>)) -> Command<Message> {
+        self.team1 = team1;
+        self.team2 = team2;
+        for player in self.team1.iter_mut().chain(self.team2.iter_mut()) {
+            player.apply_card_kind(&self.config);
+        }
+        self.apply_ship_info();
+        self.fetch_stats()
+    }
+
+    fn apply_ship_info(&mut self) {
+        for player in self.team1.iter_mut().chain(self.team2.iter_mut()) {
+            player.apply_ship_info(&self.ships);
+        }
+    }
+
+    // Fetch the ship encyclopedia when there is none yet, the game was updated or
+    // the stored one is getting old
+    fn download_ships(&mut self) -> Command<Message> {
+        let game_version = self
+            .arena
+            .as_ref()
+            .map(|arena| arena.client_version_from_exe.clone())
+            .unwrap_or_default();
+        if self.downloading_ships
+            || self.config.application_id.is_empty()
+            || !self.ships.is_outdated(&game_version, cache::now_secs())
+        {
+            return Command::none();
+        }
+        self.downloading_ships = true;
+        let provider = Arc::clone(&self.stats_provider);
+        let region = self.config.region;
+        Command::perform(
+            worker::run(move || {
+                stats::fetch_ships(provider.as_ref(), region).map(|mut ships| {
+                    ships.game_version = game_version;
+                    ships.downloaded_at = cache::now_secs();
+                    ships
+                })
+            }),
+            Message::ShipsDownloaded,
+        )
+    }
+
+    // One command per player so each card fills in as soon as its lookup finishes
+    fn fetch_stats(&mut self) -> Command<Message> {
+        let missing_application_id = self.config.application_id.is_empty();
+        for player in self.team1.iter_mut().chain(self.team2.iter_mut()) {
+            if player.status == StatsStatus::Bot {
+                continue;
+            }
+            player.status = if missing_application_id {
+                StatsStatus::Failed("no application id configured".to_string())
+            } else {
+                StatsStatus::Loading
+            };
+        }
+        if missing_application_id {
+            warn!("No application id configured, skipping stats lookup");
+            return Command::none();
+        }
+
+        let region = self.config.region;
+        let players = self
+            .team1
+            .iter()
+            .chain(&self.team2)
+            .filter(|player| player.status != StatsStatus::Bot);
+        Command::batch(players.map(|player| {
+            let provider = Arc::clone(&self.stats_provider);
+            let cache = Arc::clone(&self.stats_cache);
+            let snapshots = Arc::clone(&self.stats_snapshots);
+            let (name, ship_id) = (player.name.clone(), player.ship_id);
+            let key = name.clone();
+            Command::perform(
+                worker::run(move || {
+                    cache::fetch_cached(
+                        provider.as_ref(),
+                        &cache,
+                        &snapshots,
+                        region,
+                        &name,
+                        ship_id,
+                    )
+                }),
+                move |result| Message::PlayerStatsLoaded(region, key, ship_id, result),
+            )
+        }))
+    }
+
+    // The cache file is rewritten as a whole, so only once all cards are filled in
+    fn save_stats_cache(&self) -> Command<Message> {
+        let cache = Arc::clone(&self.stats_cache);
+        let snapshots = Arc::clone(&self.stats_snapshots);
+        Command::perform(
+            worker::run(move || {
+                cache::lock(&cache).save();
+                cache::lock(&snapshots).save();
+            }),
+            |_| Message::Nothing,
+        )
+    }
+
+    fn teams_from_arena(arena: &ArenaInfo) -> (Vec<Player>, Vec<Player>) {
+        let (allies, enemies) = arena.split_teams();
+        let own_division = arena.own_division();
+        let to_players = |vehicles: Vec<&ArenaVehicle>| {
+            vehicles
+                .into_iter()
+                .map(|vehicle| Player::from_arena_vehicle(vehicle, own_division))
+                .collect()
+        };
+        (to_players(allies), to_players(enemies))
+    }
+
+    fn player_stats_view(&self, player: &Player) -> Element<'_, Message> {
+        if let Some(placeholder) = player.recent_placeholder(self.config.stats_window) {
+            return styled_text_with_color(placeholder, GRAY_COLOR)
+                .width(Length::FillPortion(4))
+                .into();
+        }
+        let (account, ship) = player.numbers(self.config.stats_window);
+        // Damage and frags are only comparable against the ship's expected values
+        let expected = self.expected_values.get(player.ship_id);
+
+        let pr = ship
+            .and_then(|ship| ship.pr)
+            .map(|pr| (format!("{}", pr), Rating::from_pr(pr as f32).color()));
+        let account_battles = account.map(|account| (format!("{}", account.battles), GREEN_COLOR));
+        let account_winrate = account.map(|account| {
+            (
+                format!("{:.1}%", account.winrate),
+                Rating::from_winrate(account.winrate).color(),
+            )
+        });
+        let ship_battles = ship.map(|ship| (format!("{}", ship.battles), GREEN_COLOR));
+        let ship_winrate = ship.map(|ship| {
+            (
+                format!("{:.1}%", ship.winrate),
+                Rating::from_winrate(ship.winrate).color(),
+            )
+        });
+        let avg_damage = ship.map(|ship| {
+            let color = expected.map_or(TEXT_COLOR, |values| {
+                Rating::from_ratio(ship.avg_damage / values.damage).color()
+            });
+            (format!("{:.0}", ship.avg_damage), color)
+        });
+        let avg_frags = ship.map(|ship| {
+            let color = expected.map_or(TEXT_COLOR, |values| {
+                Rating::from_ratio(ship.frags / values.frags).color()
+            });
+            (format!("{:.2}", ship.frags), color)
+        });
+
+        let ship_name = match &player.ship_info {
+            Some(info) => row![
+                svg(svg::Handle::from_memory(info.class.icon()))
+                    .width(Length::Fixed(16.0))
+                    .height(Length::Fixed(16.0)),
+                styled_text(info.tier_label()),
+                styled_text(&player.ship_name)
+            ]
+            .spacing(5)
+            .align_items(iced::Alignment::Center),
+            None => row![styled_text(&player.ship_name)],
+        };
+
+        row![
+            column![ship_name, stat_row("PR: ", pr)]
+                .spacing(4)
+                .width(Length::FillPortion(1)),
+            column![
+                stat_row("Acc Battles: ", account_battles),
+                stat_row("Acc WR: ", account_winrate),
+            ]
+            .spacing(4)
+            .width(Length::FillPortion(1)),
+            column![
+                stat_row("Ship Battles: ", ship_battles),
+                stat_row("Ship WR: ", ship_winrate),
+            ]
+            .spacing(4)
+            .width(Length::FillPortion(1)),
+            column![
+                stat_row("Avg Dmg: ", avg_damage),
+                stat_row("Avg Frags: ", avg_frags),
+            ]
+            .spacing(4)
+            .width(Length::FillPortion(1))
+        ]
+        .spacing(20)
+        .width(Length::FillPortion(4))
+        .into()
+    }
+
+    fn player_details_view(&self, details: &PlayerDetails) -> Element<'_, Message> {
+        let mut header = row![styled_text_with_size(&details.name, 20).width(Length::Fill)]
+            .spacing(8)
+            .align_items(iced::Alignment::Center);
+        // A placeholder means no account could be looked up, so there is no profile to open
+        if details.placeholder.is_none() {
+            header = header.push(
+                button(styled_text("Open profile"))
+                    .style(theme::Button::Custom(Box::new(TagButton {
+                        selected: true,
+                    })))
+                    .on_press(Message::OpenPlayerProfile(
+                        details.account_id,
+                        details.name.clone(),
+                    ))
+                    .padding([4, 12]),
+            );
+        }
+        let header = header.push(
+            button(styled_text("Close"))
+                .style(theme::Button::Custom(Box::new(TagButton {
+                    selected: false,
+                })))
+                .on_press(Message::ClosePlayerDetails)
+                .padding([4, 12]),
+        );
+
+        let section = |title: &str, content: Element<'static, Message>| {
+            column![styled_text_with_color(title, GRAY_COLOR), content].spacing(8)
+        };
+        let chart_height = Length::Fixed(160.0);
+
+        let ships: Element<Message> = match (&details.ships, details.placeholder) {
+            (_, Some(placeholder)) => styled_text_with_color(placeholder, GRAY_COLOR).into(),
+            (None, None) => styled_text_with_color("Loading ships...", GRAY_COLOR).into(),
+            (Some(Err(e)), None) => {
+                styled_text_with_color(&format!("Ships unavailable: {}", e), RED_COLOR).into()
+            }
+            (Some(Ok(ships)), None) => {
+                let top_ships = player_details::top_ships(ships).into_iter().fold(
+                    column![].spacing(4),
+                    |list, (ship_id, stats)| {
+                        let (tier, name) = match self.ships.get(ship_id) {
+                            Some(info) => (info.tier_label(), info.name.clone()),
+                            None => ("", ship_id.to_string()),
+                        };
+                        let expected = self.expected_values.get(ship_id);
+                        let damage_color = expected.map_or(TEXT_COLOR, |values| {
+                            Rating::from_ratio(stats.avg_damage() / values.damage).color()
+                        });
+                        list.push(
+                            row![
+                                styled_text(tier).width(Length::FillPortion(1)),
+                                styled_text(&name).width(Length::FillPortion(4)),
+                                stat_row(
+                                    "Battles: ",
+                                    Some((stats.battles.to_string(), GREEN_COLOR))
+                                )
+                                .width(Length::FillPortion(3)),
+                                stat_row(
+                                    "WR: ",
+                                    Some((
+                                        format!("{:.1}%", stats.winrate()),
+                                        Rating::from_winrate(stats.winrate()).color()
+                                    ))
+                                )
+                                .width(Length::FillPortion(3)),
+                                stat_row(
+                                    "Avg Dmg: ",
+                                    Some((format!("{:.0}", stats.avg_damage()), damage_color))
+                                )
+                                .width(Length::FillPortion(3)),
+                            ]
+                            .spacing(10),
+                        )
+                    },
+                );
+
+                let class_bars = player_details::class_totals(ships, &self.ships)
+                    .into_iter()
+                    .map(|(class, stats)| charts::Bar {
+                        label: class.short().to_string(),
+                        value: stats.winrate(),
+                        value_label: format!("{:.1}%", stats.winrate()),
+                        color: Rating::from_winrate(stats.winrate()).color(),
+                    })
+                    .collect();
+                let tier_bars = player_details::tier_battles(ships, &self.ships)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, battles)| charts::Bar {
+                        label: ships::tier_label(index as u8 + 1).to_string(),
+                        value: battles as f32,
+                        value_label: battles.to_string(),
+                        color: DISCORD_BLUE,
+                    })
+                    .collect();
+
+                column![
+                    section("Top ships", top_ships.into()),
+                    row![
+                        section(
+                            "Winrate by class",
+                            canvas(charts::BarChart { bars: class_bars })
+                                .width(Length::Fill)
+                                .height(chart_height)
+                                .into()
+                        )
+                        .width(Length::FillPortion(1)),
+                        section(
+                            "Battles by tier",
+                            canvas(charts::BarChart { bars: tier_bars })
+                                .width(Length::Fill)
+                                .height(chart_height)
+                                .into()
+                        )
+                        .width(Length::FillPortion(1)),
+                    ]
+                    .spacing(20)
+                ]
+                .spacing(20)
+                .into()
+            }
+        };
+
+        let trend: Element<Message> = if details.trend.len() < 2 {
+            styled_text_with_color(
+                "Meet this player in a few more battles to see a trend",
+                GRAY_COLOR,
+            )
+            .into()
+        } else {
+            canvas(charts::LineChart {
+                points: details.trend.clone(),
+                color: DISCORD_BLUE,
+            })
+            .width(Length::Fill)
+            .height(chart_height)
+            .into()
+        };
+
+        container(column![header, ships, section("Account winrate when we met", trend)].spacing(20))
+            .padding(20)
+            .width(Length::Fill)
+            .style(theme::Container::Custom(Box::new(
+                CustomContainer::PlayerCard(CardKind::Normal),
+            )))
+            .into()
+    }
+
+    fn session_view(&self) -> Element<'_, Message> {
+        let sessions = self.sessions.sessions();
+        let latest = sessions.len() - 1;
+        let index = self.selected_session.unwrap_or(latest);
+        let session = &sessions[index];
+
+        let choices: Vec<SessionChoice> = sessions
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, session)| SessionChoice {
+                index,
+                label: match (index == latest, session.battles.first()) {
+                    (true, _) => "Current session".to_string(),
+                    (false, Some(first)) => {
+                        format!("{} ({} battles)", first.date_time, session.battles.len())
+                    }
+                    (false, None) => "Empty session".to_string(),
+                },
+            })
+            .collect();
+        let selected = choices.iter().find(|choice| choice.index == index).cloned();
+        let mut header = row![pick_list(choices, selected, Message::SelectSession)
+            .style(theme::PickList::Custom(
+                Rc::new(TopBarPickList),
+                Rc::new(TopBarPickList)
+            ))
+            .padding(5)]
+        .spacing(10)
+        .align_items(iced::Alignment::Center);
+        if index == latest {
+            header = header.push(
+                button(styled_text("Reset session"))
+                    .style(theme::Button::Custom(Box::new(TagButton {
+                        selected: false,
+                    })))
+                    .on_press(Message::ResetSession)
+                    .padding([4, 12]),
+            );
+        }
+
+        let totals = session.totals();
+        let pr = session.pr(&self.expected_values);
+        let pr_delta = pr.zip(session.baseline_pr).map(|(pr, baseline)| {
+            let delta = pr - baseline;
+            let color = if delta >= 0.0 {
+                LIGHT_GREEN_COLOR
+            } else {
+                LIGHT_RED_COLOR
+            };
+            (format!("{:+.0}", delta), color)
+        });
+        let has_battles = totals.battles > 0;
+        let summary = row![
+            column![
+                stat_row("Battles: ", Some((totals.battles.to_string(), GREEN_COLOR))),
+                stat_row(
+                    "Wins / Losses: ",
+                    Some((
+                        format!("{} / {}", totals.wins, session.losses()),
+                        TEXT_COLOR
+                    ))
+                ),
+            ]
+            .spacing(4)
+            .width(Length::FillPortion(1)),
+            column![
+                stat_row(
+                    "WR: ",
+                    has_battles.then(|| {
+                        (
+                            format!("{:.1}%", totals.winrate()),
+                            Rating::from_winrate(totals.winrate()).color(),
+                        )
+                    })
+                ),
+                stat_row(
+                    "Avg Dmg: ",
+                    has_battles.then(|| (format!("{:.0}", totals.avg_damage()), TEXT_COLOR))
+                ),
+            ]
+            .spacing(4)
+            .width(Length::FillPortion(1)),
+            column![
+                stat_row(
+                    "Avg Frags: ",
+                    has_battles.then(|| (format!("{:.2}", totals.avg_frags()), TEXT_COLOR))
+                ),
+                stat_row(
+                    "PR: ",
+                    pr.map(|pr| (format!("{:.0}", pr), Rating::from_pr(pr).color()))
+                ),
+            ]
+            .spacing(4)
+            .width(Length::FillPortion(1)),
+            column![stat_row("PR vs lifetime: ", pr_delta)]
+                .spacing(4)
+                .width(Length::FillPortion(1)),
+        ]
+        .spacing(20);
+
+        let ships = session.ship_totals().into_iter().fold(
+            column![].spacing(4),
+            |list, (ship_id, ship_name, stats)| {
+                let (tier, name) = match self.ships.get(ship_id) {
+                    Some(info) => (info.tier_label(), info.name.clone()),
+                    None => ("", ship_name),
+                };
+                list.push(
+                    row![
+                        styled_text(tier).width(Length::FillPortion(1)),
+                        styled_text(&name).width(Length::FillPortion(4)),
+                        stat_row("Battles: ", Some((stats.battles.to_string(), GREEN_COLOR)))
+                            .width(Length::FillPortion(3)),
+                        stat_row(
+                            "WR: ",
+                            Some((
+                                format!("{:.1}%", stats.winrate()),
+                                Rating::from_winrate(stats.winrate()).color()
+                            ))
+                        )
+                        .width(Length::FillPortion(3)),
+                        stat_row(
+                            "Avg Dmg: ",
+                            Some((format!("{:.0}", stats.avg_damage()), TEXT_COLOR))
+                        )
+                        .width(Length::FillPortion(3)),
+                        stat_row(
+                            "Avg Frags: ",
+                            Some((format!("{:.2}", stats.avg_frags()), TEXT_COLOR))
+                        )
+                        .width(Length::FillPortion(3)),
+                    ]
+                    .spacing(10),
+                )
+            },
+        );
+
+        let mut content = column![header, summary, ships].spacing(20);
+        let without_results = session.battles.len() - totals.battles as usize;
+        if without_results > 0 {
+            content = content.push(styled_text_with_color(
+                &format!(
+                    "{} battles without results in their replay",
+                    without_results
+                ),
+                GRAY_COLOR,
+            ));
+        }
+
+        container(content)
+            .padding(20)
+            .width(Length::Fill)
+            .style(theme::Container::Custom(Box::new(
+                CustomContainer::PlayerCard(CardKind::Normal),
+            )))
+            .into()
+    }
+
+    fn note_editor(&self, draft: &PlayerNote) -> Element<'_, Message> {
+        let tags = self
+            .config
+            .note_tags
+            .iter()
+            .fold(row![].spacing(5), |tags, tag| {
+                let selected = draft.tags.contains(tag);
+                tags.push(
+                    button(styled_text(tag))
+                        .style(theme::Button::Custom(Box::new(TagButton { selected })))
+                        .on_press(Message::NoteTagToggled(tag.clone()))
+                        .padding([2, 8]),
+                )
+            });
+        let blacklist = button(styled_text_with_color(
+            "Blacklist",
+            if draft.blacklisted {
+                BLACKLIST_BORDER_COLOR
+            } else {
+                TEXT_COLOR
+            },
+        ))
+        .style(theme::Button::Custom(Box::new(TagButton {
+            selected: draft.blacklisted,
+        })))
+        .on_press(Message::NoteBlacklistToggled)
+        .padding([2, 8]);
+        let actions = row![
+            button(styled_text("Save"))
+                .style(theme::Button::Custom(Box::new(TagButton {
+                    selected: true
+                })))
+                .on_press(Message::SaveNote)
+                .padding([4, 12]),
+            button(styled_text("Cancel"))
+                .style(theme::Button::Custom(Box::new(TagButton {
+                    selected: false
+                })))
+                .on_press(Message::CancelNote)
+                .padding([4, 12]),
+        ]
+        .spacing(8);
+
+        container(
+            column![
+                styled_text_with_size(&format!("Note on {}", draft.name), 16),
+                text_input("Write a note about this player...", &draft.text)
+                    .on_input(Message::NoteTextChanged)
+                    .on_submit(Message::SaveNote)
+                    .style(theme::TextInput::Custom(Box::new(NoteInput)))
+                    .padding(6)
+                    .size(14),
+                tags,
+                blacklist,
+                actions
+            ]
+            .spacing(8),
+        )
+        .padding(16)
+        .width(Length::Fixed(420.0))
+        .style(theme::Container::Custom(Box::new(CustomContainer::Tooltip)))
+        .into()
+    }
+
+    fn replay_browser_view(&self) -> Element<'_, Message> {
+        let header = row(ReplaySortKey::ALL
+            .iter()
+            .map(|&key| {
+                let arrow = match (key == self.replay_sort, self.replay_sort_descending) {
+                    (true, true) => " ▼",
+                    (true, false) => " ▲",
+                    (false, _) => "",
+                };
+                button(styled_text(&format!("{}{}", key.label(), arrow)))
+                    .style(theme::Button::Custom(Box::new(PlayerNameButton)))
+                    .on_press(Message::SortReplays(key))
+                    .width(Length::FillPortion(1))
+                    .into()
+            })
+            .collect())
+        .spacing(20)
+        .padding([0, 10]);
+
+        let rows = self.replays.iter().map(|replay| -> Element<Message> {
+            button(
+                row![
+                    styled_text(&replay.date_time).width(Length::FillPortion(1)),
+                    styled_text(&replay.map).width(Length::FillPortion(1)),
+                    styled_text(&replay.mode).width(Length::FillPortion(1)),
+                    styled_text(&replay.ship).width(Length::FillPortion(1)),
+                    styled_text(&replay.duration_label()).width(Length::FillPortion(1)),
+                ]
+                .spacing(20),
+            )
+            .style(theme::Button::Custom(Box::new(ReplayRowButton)))
+            .on_press(Message::OpenReplay(replay.path.clone()))
+            .padding(10)
+            .width(Length::Fill)
+            .into()
+        });
+
+        let mut list = column![header].spacing(5).padding(20).width(Length::Fill);
+        if self.replays.is_empty() {
+            list = list.push(styled_text_with_color("No replays found", GRAY_COLOR));
+        }
+        rows.fold(list, |list, row| list.push(row)).into()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -52,7 +1120,38 @@ enum Message {
     WindowResized(u32, u32),
     OpenGithub,
     OpenFolderDialog,
-    PlayerNameClicked(String),
+    ShowPlayerDetails(i64, String),
+    ClosePlayerDetails,
+    PlayerDetailsLoaded(i64, Result<Vec<(u64, BattleStats)>, StatsError>),
+    OpenPlayerProfile(i64, String),
+    ClanTagClicked(i64, String),
+    BattleStarted,
+    BattleEnded,
+    ShowTeams,
+    ShowReplayBrowser,
+    ShowSession,
+    SelectSession(SessionChoice),
+    ResetSession,
+    SessionBattlesFound(Vec<SessionBattle>),
+    SessionBaselineLoaded(Result<Vec<(u64, BattleStats)>, StatsError>),
+    ReplaysScanned(Vec<ReplaySummary>),
+    SortReplays(ReplaySortKey),
+    OpenReplay(PathBuf),
+    ReplayDurationLoaded(PathBuf, Option<u32>),
+    // Keyed like the cache by region, name and ship, account ids are only known
+    // once the lookup is done
+    PlayerStatsLoaded(Region, String, u64, Result<CachedStats, StatsError>),
+    OutcomesFound(Vec<(String, Outcome)>),
+    ShipsDownloaded(Result<ShipDatabase, StatsError>),
+    RegionSelected(Region),
+    EditNote(i64, String),
+    NoteTextChanged(String),
+    NoteTagToggled(String),
+    NoteBlacklistToggled,
+    SaveNote,
+    CancelNote,
+    TeamSortSelected(Team, TeamSortKey),
+    StatsWindowSelected(StatsWindow),
     Nothing,
 }
 
@@ -60,6 +1159,8 @@ enum Message {
 enum Icon {
     Home,
     Folder,
+    History,
+    Session,
 }
 
 impl Icon {
@@ -69,6 +1170,10 @@ impl Icon {
             Icon::Folder => {
                 include_bytes!("../assets/folder_80dp_FFF_FILL0_wght400_GRAD0_opsz48.svg")
             }
+            Icon::History => {
+                include_bytes!("../assets/history_80dp_FFF_FILL0_wght400_GRAD0_opsz48.svg")
+            }
+            Icon::Session => include_bytes!("../assets/session.svg"),
         }
     }
 
@@ -119,6 +1224,180 @@ impl button::StyleSheet for PlayerNameButton {
     }
 }
 
+// Add custom button style for clan tags
+#[derive(Debug, Clone, Copy)]
+struct ClanTagButton;
+
+impl button::StyleSheet for ClanTagButton {
+    type Style = Theme;
+
+    fn active(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            text_color: CLAN_TAG_COLOR,
+            ..Default::default()
+        }
+    }
+
+    fn hovered(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            text_color: DISCORD_BLUE,
+            ..Default::default()
+        }
+    }
+
+    fn pressed(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            text_color: DISCORD_BLUE,
+            ..Default::default()
+        }
+    }
+}
+
+// Add custom button style for replay browser rows
+#[derive(Debug, Clone, Copy)]
+struct ReplayRowButton;
+
+impl button::StyleSheet for ReplayRowButton {
+    type Style = Theme;
+
+    fn active(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            background: Some(iced::Background::Color(DISCORD_CARD)),
+            border_radius: 8.0.into(),
+            text_color: TEXT_COLOR,
+            ..Default::default()
+        }
+    }
+
+    fn hovered(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            background: Some(iced::Background::Color(DISCORD_CARD_HOVERED)),
+            border_radius: 8.0.into(),
+            text_color: TEXT_COLOR,
+            ..Default::default()
+        }
+    }
+}
+
+// Add custom button style for note tags, filled when selected
+#[derive(Debug, Clone, Copy)]
+struct TagButton {
+    selected: bool,
+}
+
+impl button::StyleSheet for TagButton {
+    type Style = Theme;
+
+    fn active(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            background: Some(iced::Background::Color(if self.selected {
+                DISCORD_BLUE
+            } else {
+                DISCORD_CARD_HOVERED
+            })),
+            border_radius: 8.0.into(),
+            text_color: TEXT_COLOR,
+            ..Default::default()
+        }
+    }
+
+    fn hovered(&self, _style: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            border_width: 1.0,
+            border_color: DISCORD_BLUE,
+            ..self.active(_style)
+        }
+    }
+}
+
+// Add custom text input style for player notes
+#[derive(Debug, Clone, Copy)]
+struct NoteInput;
+
+impl text_input::StyleSheet for NoteInput {
+    type Style = Theme;
+
+    fn active(&self, _style: &Self::Style) -> text_input::Appearance {
+        text_input::Appearance {
+            background: iced::Background::Color(DISCORD_CARD),
+            border_radius: 4.0.into(),
+            border_width: 1.0,
+            border_color: DISCORD_CARD_HOVERED,
+            icon_color: GRAY_COLOR,
+        }
+    }
+
+    fn focused(&self, _style: &Self::Style) -> text_input::Appearance {
+        text_input::Appearance {
+            border_color: DISCORD_BLUE,
+            ..self.active(_style)
+        }
+    }
+
+    fn placeholder_color(&self, _style: &Self::Style) -> Color {
+        GRAY_COLOR
+    }
+
+    fn value_color(&self, _style: &Self::Style) -> Color {
+        TEXT_COLOR
+    }
+
+    fn disabled_color(&self, _style: &Self::Style) -> Color {
+        GRAY_COLOR
+    }
+
+    fn selection_color(&self, _style: &Self::Style) -> Color {
+        DISCORD_BLUE
+    }
+
+    fn disabled(&self, _style: &Self::Style) -> text_input::Appearance {
+        self.active(_style)
+    }
+}
+
+// Add custom pick list style for the top bar
+#[derive(Debug, Clone, Copy)]
+struct TopBarPickList;
+
+impl pick_list::StyleSheet for TopBarPickList {
+    type Style = Theme;
+
+    fn active(&self, _style: &Self::Style) -> pick_list::Appearance {
+        pick_list::Appearance {
+            text_color: TEXT_COLOR,
+            placeholder_color: GRAY_COLOR,
+            handle_color: TEXT_COLOR,
+            background: iced::Background::Color(DISCORD_CARD),
+            border_radius: 4.0.into(),
+            border_width: 0.0,
+            border_color: Color::TRANSPARENT,
+        }
+    }
+
+    fn hovered(&self, _style: &Self::Style) -> pick_list::Appearance {
+        pick_list::Appearance {
+            background: iced::Background::Color(DISCORD_CARD_HOVERED),
+            ..self.active(_style)
+        }
+    }
+}
+
+impl menu::StyleSheet for TopBarPickList {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> menu::Appearance {
+        menu::Appearance {
+            text_color: TEXT_COLOR,
+            background: iced::Background::Color(DISCORD_CARD),
+            border_width: 0.0,
+            border_radius: 4.0.into(),
+            border_color: Color::TRANSPARENT,
+            selected_text_color: Color::WHITE,
+            selected_background: iced::Background::Color(DISCORD_BLUE),
+        }
+    }
+}
+
 // Add custom button style for icons
 #[derive(Debug, Clone, Copy)]
 struct IconButton {
@@ -195,292 +1474,60 @@ impl Application for StatsViewer {
     type Message = Message;
     type Theme = Theme;
     type Executor = iced::executor::Default;
-    type Flags = ();
+    // Whether to show the sample teams instead of the live arena
+    type Flags = bool;
 
-    fn new(_flags: ()) -> (Self, iced::Command<Message>) {
+    fn new(demo: bool) -> (Self, iced::Command<Message>) {
         info!("Initializing StatsViewer");
         let config = Config::load();
         let last_folder_path = config.selected_folder.clone();
-        // Initialize with sample data
-        let team1 = vec![
-            Player {
-                name: "Alpha".to_string(),
-                winrate: 49.96,
-                battles: 2754,
-                ship_name: "Ship1".to_string(),
-                ship_winrate: 48.5,
-                ship_battles: 156,
-                pr: 856,
-                avg_damage: 84849.0,
-                frags: 0.8,
-            },
-            Player {
-                name: "Beta".to_string(),
-                winrate: 47.22,
-                battles: 4852,
-                ship_name: "Ship2".to_string(),
-                ship_winrate: 51.2,
-                ship_battles: 342,
-                pr: 1425,
-                avg_damage: 132932.0,
-                frags: 1.2,
-            },
-            Player {
-                name: "Charlie".to_string(),
-                winrate: 43.45,
-                battles: 1991,
-                ship_name: "Ship3".to_string(),
-                ship_winrate: 46.8,
-                ship_battles: 89,
-                pr: 485,
-                avg_damage: 57493.0,
-                frags: 0.6,
-            },
-            Player {
-                name: "Delta".to_string(),
-                winrate: 45.02,
-                battles: 844,
-                ship_name: "Ship4".to_string(),
-                ship_winrate: 44.9,
-                ship_battles: 234,
-                pr: 892,
-                avg_damage: 71441.0,
-                frags: 0.7,
-            },
-            Player {
-                name: "Echo".to_string(),
-                winrate: 48.53,
-                battles: 5110,
-                ship_name: "Ship5".to_string(),
-                ship_winrate: 52.1,
-                ship_battles: 445,
-                pr: 447,
-                avg_damage: 45591.0,
-                frags: 0.9,
-            },
-            Player {
-                name: "Foxtrot".to_string(),
-                winrate: 47.3,
-                battles: 9349,
-                ship_name: "Ship6".to_string(),
-                ship_winrate: 49.9,
-                ship_battles: 678,
-                pr: 1248,
-                avg_damage: 51313.0,
-                frags: 1.1,
-            },
-            Player {
-                name: "Mike".to_string(),
-                winrate: 51.23,
-                battles: 3245,
-                ship_name: "Ship13".to_string(),
-                ship_winrate: 52.8,
-                ship_battles: 234,
-                pr: 1256,
-                avg_damage: 98765.0,
-                frags: 1.3,
-            },
-            Player {
-                name: "November".to_string(),
-                winrate: 48.76,
-                battles: 4123,
-                ship_name: "Ship14".to_string(),
-                ship_winrate: 47.5,
-                ship_battles: 345,
-                pr: 892,
-                avg_damage: 65432.0,
-                frags: 0.8,
-            },
-            Player {
-                name: "Oscar".to_string(),
-                winrate: 52.34,
-                battles: 2876,
-                ship_name: "Ship15".to_string(),
-                ship_winrate: 53.2,
-                ship_battles: 456,
-                pr: 1456,
-                avg_damage: 112345.0,
-                frags: 1.4,
-            },
-            Player {
-                name: "Papa".to_string(),
-                winrate: 46.78,
-                battles: 5678,
-                ship_name: "Ship16".to_string(),
-                ship_winrate: 45.9,
-                ship_battles: 567,
-                pr: 678,
-                avg_damage: 45678.0,
-                frags: 0.7,
-            },
-            Player {
-                name: "Quebec".to_string(),
-                winrate: 50.12,
-                battles: 3456,
-                ship_name: "Ship17".to_string(),
-                ship_winrate: 51.5,
-                ship_battles: 678,
-                pr: 1234,
-                avg_damage: 87654.0,
-                frags: 1.0,
-            },
-            Player {
-                name: "Romeo".to_string(),
-                winrate: 49.87,
-                battles: 4321,
-                ship_name: "Ship18".to_string(),
-                ship_winrate: 48.7,
-                ship_battles: 789,
-                pr: 987,
-                avg_damage: 76543.0,
-                frags: 0.9,
-            },
-        ];
-
-        let team2 = vec![
-            Player {
-                name: "Golf".to_string(),
-                winrate: 49.92,
-                battles: 2644,
-                ship_name: "Ship7".to_string(),
-                ship_winrate: 53.4,
-                ship_battles: 223,
-                pr: 1350,
-                avg_damage: 103170.0,
-                frags: 1.2,
-            },
-            Player {
-                name: "Hotel".to_string(),
-                winrate: 49.49,
-                battles: 2623,
-                ship_name: "Ship8".to_string(),
-                ship_winrate: 47.8,
-                ship_battles: 167,
-                pr: 1121,
-                avg_damage: 139917.0,
-                frags: 1.1,
-            },
-            Player {
-                name: "India".to_string(),
-                winrate: 49.74,
-                battles: 2280,
-                ship_name: "Ship9".to_string(),
-                ship_winrate: 50.2,
-                ship_battles: 445,
-                pr: 1236,
-                avg_damage: 105548.0,
-                frags: 1.0,
-            },
-            Player {
-                name: "Juliet".to_string(),
-                winrate: 47.21,
-                battles: 2923,
-                ship_name: "Ship10".to_string(),
-                ship_winrate: 46.9,
-                ship_battles: 332,
-                pr: 892,
-                avg_damage: 92047.0,
-                frags: 0.8,
-            },
-            Player {
-                name: "Kilo".to_string(),
-                winrate: 53.43,
-                battles: 3509,
-                ship_name: "Ship11".to_string(),
-                ship_winrate: 55.6,
-                ship_battles: 221,
-                pr: 962,
-                avg_damage: 66757.0,
-                frags: 1.3,
-            },
-            Player {
-                name: "Lima".to_string(),
-                winrate: 51.96,
-                battles: 4419,
-                ship_name: "Ship12".to_string(),
-                ship_winrate: 50.8,
-                ship_battles: 554,
-                pr: 1009,
-                avg_damage: 119177.0,
-                frags: 1.4,
-            },
-            Player {
-                name: "Sierra".to_string(),
-                winrate: 48.45,
-                battles: 3789,
-                ship_name: "Ship19".to_string(),
-                ship_winrate: 49.2,
-                ship_battles: 456,
-                pr: 876,
-                avg_damage: 67890.0,
-                frags: 0.9,
-            },
-            Player {
-                name: "Tango".to_string(),
-                winrate: 52.67,
-                battles: 2987,
-                ship_name: "Ship20".to_string(),
-                ship_winrate: 54.1,
-                ship_battles: 567,
-                pr: 1345,
-                avg_damage: 98765.0,
-                frags: 1.5,
-            },
-            Player {
-                name: "Uniform".to_string(),
-                winrate: 47.89,
-                battles: 4567,
-                ship_name: "Ship21".to_string(),
-                ship_winrate: 46.8,
-                ship_battles: 678,
-                pr: 765,
-                avg_damage: 54321.0,
-                frags: 0.7,
-            },
-            Player {
-                name: "Victor".to_string(),
-                winrate: 50.34,
-                battles: 3456,
-                ship_name: "Ship22".to_string(),
-                ship_winrate: 51.7,
-                ship_battles: 789,
-                pr: 1123,
-                avg_damage: 87654.0,
-                frags: 1.1,
-            },
-            Player {
-                name: "Whiskey".to_string(),
-                winrate: 49.56,
-                battles: 4321,
-                ship_name: "Ship23".to_string(),
-                ship_winrate: 48.9,
-                ship_battles: 890,
-                pr: 987,
-                avg_damage: 76543.0,
-                frags: 0.8,
-            },
-            Player {
-                name: "Xray".to_string(),
-                winrate: 51.78,
-                battles: 2987,
-                ship_name: "Ship24".to_string(),
-                ship_winrate: 52.5,
-                ship_battles: 567,
-                pr: 1234,
-                avg_damage: 98765.0,
-                frags: 1.2,
-            },
-        ];
-
-        (
-            Self {
-                team1,
-                team2,
-                config,
-                last_folder_path,
-            },
-            Command::none(),
-        )
+        let stats_provider = Arc::new(RateLimited::new(
+            WargamingApi::new(config.application_id.clone(), config.api_base_url.clone()),
+            config.requests_per_second,
+        ));
+        let stats_cache = Arc::new(Mutex::new(StatsCache::load(
+            &StatsCache::path(),
+            config.cache_ttl_hours * 3600,
+            config.cache_max_entries,
+        )));
+
+        let mut viewer = Self {
+            team1: Vec::new(),
+            team2: Vec::new(),
+            config,
+            last_folder_path,
+            demo,
+            view: View::Teams,
+            replays: Vec::new(),
+            duration_scan: Vec::new(),
+            replay_sort: ReplaySortKey::Date,
+            replay_sort_descending: true,
+            stats_provider,
+            stats_cache,
+            stats_snapshots: Arc::new(Mutex::new(SnapshotStore::load(&SnapshotStore::path()))),
+            expected_values: ExpectedValues::load(&ExpectedValues::path()),
+            ships: ShipDatabase::load(&ShipDatabase::path()),
+            downloading_ships: false,
+            arena: None,
+            history: BattleHistory::load(&BattleHistory::path()),
+            encounters: EncounterLog::load(&EncounterLog::path()),
+            notes: NotesStore::load(&NotesStore::path()),
+            note_draft: None,
+            player_details: None,
+            sessions: SessionStore::load(&SessionStore::path()),
+            selected_session: None,
+        };
+        let command = if demo {
+            (viewer.team1, viewer.team2) = demo::sample_teams();
+            viewer.ships = demo::sample_ships();
+            viewer.apply_ship_info();
+            Command::none()
+        } else {
+            viewer.calibrate_win_model();
+            Command::batch([viewer.load_arena(), viewer.find_outcomes()])
+        };
+
+        (viewer, command)
     }
 
     fn title(&self) -> String {
@@ -488,14 +1535,30 @@ impl Application for StatsViewer {
     }
 
     fn subscription(&self) -> iced::Subscription<Message> {
-        iced::subscription::events().map(|event| {
+        let window_events = iced::subscription::events().map(|event| {
             if let iced::Event::Window(window_event) = event {
                 if let iced::window::Event::Resized { width, height } = window_event {
                     return Message::WindowResized(width, height);
                 }
             }
             Message::Nothing
-        })
+        });
+
+        if self.demo || self.config.selected_folder.is_none() {
+            return window_events;
+        }
+        let arena_events =
+            watcher::arena_events(self.config.replay_path()).map(|event| match event {
+                ArenaEvent::BattleStarted => Message::BattleStarted,
+                ArenaEvent::BattleEnded => Message::BattleEnded,
+            });
+        let replay_durations = if self.duration_scan.is_empty() {
+            iced::Subscription::none()
+        } else {
+            replay_browser::durations(self.duration_scan.clone())
+                .map(|(path, duration)| Message::ReplayDurationLoaded(path, duration))
+        };
+        iced::Subscription::batch([window_events, arena_events, replay_durations])
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
@@ -524,13 +1587,316 @@ impl Application for StatsViewer {
                     config.save();
                     self.config = config;
                     self.last_folder_path = self.config.selected_folder.clone();
+                    if !self.demo {
+                        return Command::batch([self.load_arena(), self.find_outcomes()]);
+                    }
+                }
+            }
+            Message::ShowPlayerDetails(account_id, name) => {
+                let trend = self.history.account_winrates(account_id);
+                let details = PlayerDetails::new(account_id, name.clone(), trend);
+                // Bots and players whose name didn't resolve have no account to look up
+                if account_id <= 0 {
+                    self.player_details = Some(details.unavailable("No account to show ships for"));
+                    return Command::none();
+                }
+                if self.config.application_id.is_empty() && !self.demo {
+                    self.player_details =
+                        Some(details.unavailable("Ships need an application id in the config"));
+                    return Command::none();
+                }
+                self.player_details = Some(details);
+                if self.demo {
+                    let ships = demo::sample_ship_stats(account_id);
+                    return Command::perform(async move { Ok(ships) }, move |result| {
+                        Message::PlayerDetailsLoaded(account_id, result)
+                    });
+                }
+                let provider = Arc::clone(&self.stats_provider);
+                let region = self.config.region;
+                return Command::perform(
+                    worker::run(move || provider.all_ship_stats(region, account_id)),
+                    move |result| Message::PlayerDetailsLoaded(account_id, result),
+                );
+            }
+            Message::ClosePlayerDetails => {
+                self.player_details = None;
+            }
+            Message::PlayerDetailsLoaded(account_id, result) => {
+                // Ignore lookups for a pane that was closed or replaced meanwhile
+                if let Some(details) = &mut self.player_details {
+                    if details.account_id == account_id {
+                        if let Err(e) = &result {
+                            warn!("Failed to load ships of {}: {}", details.name, e);
+                        }
+                        details.ships = Some(result);
+                    }
                 }
             }
-            Message::PlayerNameClicked(name) => {
-                if let Err(e) = open::that(format!("https://example.com/player/{}", name)) {
+            Message::OpenPlayerProfile(account_id, name) => {
+                let url = profile::profile_url(
+                    self.config.profile_site,
+                    &self.config.custom_profile_url,
+                    self.config.region,
+                    account_id,
+                    &name,
+                );
+                if let Err(e) = open::that(url) {
                     error!("Failed to open player profile: {}", e);
                 }
             }
+            Message::ClanTagClicked(clan_id, tag) => {
+                let url = profile::clan_url(
+                    self.config.profile_site,
+                    &self.config.custom_clan_url,
+                    self.config.region,
+                    clan_id,
+                    &tag,
+                );
+                if let Err(e) = open::that(url) {
+                    error!("Failed to open clan page: {}", e);
+                }
+            }
+            Message::BattleStarted => {
+                info!("Battle started, reloading teams");
+                // The previous battle's replay is written by now
+                return Command::batch([
+                    self.load_arena(),
+                    self.find_outcomes(),
+                    self.scan_session(),
+                ]);
+            }
+            Message::BattleEnded => {
+                // Keep showing the last battle until the next one starts
+                info!("Battle ended");
+                self.record_battle();
+                return self.scan_session();
+            }
+            Message::ShowTeams => {
+                self.view = View::Teams;
+                self.player_details = None;
+            }
+            Message::ShowReplayBrowser => {
+                self.view = View::ReplayBrowser;
+                self.player_details = None;
+                let replay_path = self.config.replay_path();
+                return Command::perform(
+                    worker::run(move || replay_browser::scan_replays(&replay_path)),
+                    Message::ReplaysScanned,
+                );
+            }
+            Message::ShowSession => {
+                self.view = View::Session;
+                self.player_details = None;
+                return self.scan_session();
+            }
+            Message::SelectSession(choice) => {
+                let latest = self.sessions.sessions().len() - 1;
+                self.selected_session = (choice.index != latest).then_some(choice.index);
+            }
+            Message::ResetSession => {
+                info!("Starting a new session");
+                self.sessions.reset();
+                self.sessions.save();
+                self.selected_session = None;
+            }
+            Message::SessionBattlesFound(battles) => {
+                if battles.is_empty() {
+                    return Command::none();
+                }
+                info!("Adding {} battles to the session", battles.len());
+                self.sessions.add_battles(battles);
+                return self.fetch_session_baseline();
+            }
+            Message::SessionBaselineLoaded(result) => match result {
+                Ok(ships) => {
+                    let session = self.sessions.current_mut();
+                    session.baseline_pr = self.expected_values.account_pr(&ships);
+                    self.sessions.save();
+                }
+                Err(e) => warn!("Failed to load lifetime stats for the session: {}", e),
+            },
+            Message::ReplaysScanned(mut replays) => {
+                // Keep the durations we already know, the rest is read in the background
+                for replay in &mut replays {
+                    replay.duration = self
+                        .replays
+                        .iter()
+                        .find(|known| known.path == replay.path)
+                        .and_then(|known| known.duration);
+                }
+                self.duration_scan = replays
+                    .iter()
+                    .filter(|replay| replay.duration.is_none())
+                    .map(|replay| replay.path.clone())
+                    .collect();
+                replay_browser::sort_replays(
+                    &mut replays,
+                    self.replay_sort,
+                    self.replay_sort_descending,
+                );
+                self.replays = replays;
+            }
+            Message::SortReplays(key) => {
+                // Clicking the active column again flips the direction
+                if self.replay_sort == key {
+                    self.replay_sort_descending = !self.replay_sort_descending;
+                } else {
+                    self.replay_sort = key;
+                    self.replay_sort_descending = false;
+                }
+                replay_browser::sort_replays(
+                    &mut self.replays,
+                    self.replay_sort,
+                    self.replay_sort_descending,
+                );
+            }
+            Message::OpenReplay(path) => match ReplayHeader::read(&path) {
+                Ok(header) => {
+                    info!("Opened replay: {:?}", path);
+                    self.view = View::Teams;
+                    self.arena = None;
+                    return self.set_teams(Self::teams_from_arena(&header.arena));
+                }
+                Err(e) => error!("Failed to open replay {:?}: {}", path, e),
+            },
+            Message::ReplayDurationLoaded(path, duration) => {
+                if let Some(replay) = self.replays.iter_mut().find(|replay| replay.path == path) {
+                    replay.duration = duration;
+                }
+            }
+            Message::PlayerStatsLoaded(region, name, ship_id, result) => {
+                // Lookups started before a region change or for an earlier battle can
+                // finish late, their numbers belong to another card
+                if region != self.config.region {
+                    return Command::none();
+                }
+                let matches = |player: &Player| player.name == name && player.ship_id == ship_id;
+                match result {
+                    Ok(cached) => {
+                        for player in self.team1.iter_mut().chain(self.team2.iter_mut()) {
+                            if matches(player) {
+                                player.apply_stats(
+                                    &cached.stats,
+                                    &cached.recent,
+                                    &self.expected_values,
+                                );
+                                player.status = if cached.stale {
+                                    StatsStatus::Stale
+                                } else {
+                                    StatsStatus::Loaded
+                                };
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Failed to load stats for {}: {}", name, e);
+                        let status = match e {
+                            StatsError::HiddenProfile => StatsStatus::Hidden,
+                            StatsError::NotFound => StatsStatus::NotFound,
+                            e => StatsStatus::Failed(e.to_string()),
+                        };
+                        for player in self.team1.iter_mut().chain(self.team2.iter_mut()) {
+                            if matches(player) {
+                                player.status = status.clone();
+                            }
+                        }
+                    }
+                }
+                let loading = self
+                    .team1
+                    .iter()
+                    .chain(&self.team2)
+                    .any(|player| player.status == StatsStatus::Loading);
+                if !loading {
+                    self.log_encounters();
+                    return self.save_stats_cache();
+                }
+            }
+            Message::RegionSelected(region) => {
+                info!("Selected region: {}", region);
+                let mut config = Config::load();
+                config.region = region;
+                config.save();
+                self.config = config;
+                if !self.demo {
+                    self.player_details = None;
+                    self.note_draft = None;
+                    for player in self.team1.iter_mut().chain(self.team2.iter_mut()) {
+                        player.clear_stats();
+                    }
+                    return self.fetch_stats();
+                }
+            }
+            Message::EditNote(account_id, name) => {
+                let note = self
+                    .notes
+                    .get(self.config.region, account_id)
+                    .cloned()
+                    .unwrap_or_default();
+                self.note_draft = Some((account_id, PlayerNote { name, ..note }));
+            }
+            Message::NoteTextChanged(text) => {
+                if let Some((_, note)) = &mut self.note_draft {
+                    note.text = text;
+                }
+            }
+            Message::NoteTagToggled(tag) => {
+                if let Some((_, note)) = &mut self.note_draft {
+                    note.toggle_tag(&tag);
+                }
+            }
+            Message::NoteBlacklistToggled => {
+                if let Some((_, note)) = &mut self.note_draft {
+                    note.blacklisted = !note.blacklisted;
+                }
+            }
+            Message::SaveNote => {
+                if let Some((account_id, note)) = self.note_draft.take() {
+                    info!("Saving note for {}", note.name);
+                    self.notes.set(self.config.region, account_id, note);
+                }
+            }
+            Message::CancelNote => {
+                self.note_draft = None;
+            }
+            Message::TeamSortSelected(team, key) => {
+                info!("Sorting {:?} by {}", team, key);
+                let mut config = Config::load();
+                match team {
+                    Team::Team1 => config.team1_sort = key,
+                    Team::Team2 => config.team2_sort = key,
+                }
+                config.save();
+                self.config = config;
+            }
+            Message::StatsWindowSelected(window) => {
+                info!("Showing {} stats", window);
+                let mut config = Config::load();
+                config.stats_window = window;
+                config.save();
+                self.config = config;
+            }
+            Message::OutcomesFound(outcomes) => {
+                info!("Found the outcome of {} battles", outcomes.len());
+                if !outcomes.is_empty() {
+                    self.history.set_outcomes(&outcomes);
+                    self.encounters.set_outcomes(&outcomes);
+                    self.calibrate_win_model();
+                }
+            }
+            Message::ShipsDownloaded(result) => {
+                self.downloading_ships = false;
+                match result {
+                    Ok(ships) => {
+                        info!("Downloaded {} ships", ships.len());
+                        ships.save(&ShipDatabase::path());
+                        self.ships = ships;
+                        self.apply_ship_info();
+                    }
+                    Err(e) => error!("Error downloading ship database: {}", e),
+                }
+            }
             Message::Nothing => {}
         }
         Command::none()
@@ -539,118 +1905,226 @@ impl Application for StatsViewer {
     fn view(&self) -> Element<Message> {
         // Force view update when folder path changes
         let _ = self.last_folder_path;
-        let create_player_view = |player: &Player| {
-            container(
-                column![row![
-                    column![button(text(&player.name).size(16))
-                        .style(theme::Button::Custom(Box::new(PlayerNameButton)))
-                        .on_press(Message::PlayerNameClicked(player.name.clone()))]
-                    .spacing(4)
-                    .width(Length::FillPortion(1)),
-                    column![
-                        styled_text(&player.ship_name),
-                        row![
-                            styled_text("PR: "),
-                            styled_text_with_color(&format!("{}", player.pr), ORANGE_COLOR)
-                        ]
-                    ]
-                    .spacing(4)
-                    .width(Length::FillPortion(1)),
-                    column![
-                        row![
-                            styled_text("Acc Battles: "),
-                            styled_text_with_color(&format!("{}", player.battles), GREEN_COLOR)
-                        ],
-                        row![
-                            styled_text("Acc WR: "),
-                            styled_text_with_color(
-                                &format!("{:.1}%", player.winrate),
-                                if player.winrate >= 50.0 {
-                                    ORANGE_COLOR
-                                } else {
-                                    RED_COLOR
-                                }
-                            )
-                        ],
-                    ]
-                    .spacing(4)
-                    .width(Length::FillPortion(1)),
-                    column![
-                        row![
-                            styled_text("Ship Battles: "),
-                            styled_text_with_color(
-                                &format!("{}", player.ship_battles),
-                                GREEN_COLOR
-                            )
-                        ],
-                        row![
-                            styled_text("Ship WR: "),
-                            styled_text_with_color(
-                                &format!("{:.1}%", player.ship_winrate),
-                                if player.ship_winrate >= 50.0 {
-                                    ORANGE_COLOR
-                                } else {
-                                    RED_COLOR
-                                }
-                            )
-                        ],
-                    ]
-                    .spacing(4)
-                    .width(Length::FillPortion(1)),
-                    // Right column (battles)
-                    column![
-                        row![
-                            styled_text("Avg Dmg: "),
-                            styled_text_with_color(
-                                &format!("{:.0}", player.avg_damage),
-                                ORANGE_COLOR
-                            )
-                        ],
-                        row![
-                            styled_text("Avg Frags: "),
-                            styled_text_with_color(&format!("{}", player.frags), GREEN_COLOR)
-                        ]
-                    ]
-                    .spacing(4)
-                    .width(Length::FillPortion(1))
-                ]
-                .spacing(20)
-                .width(Length::Fill)]
-                .spacing(5)
-                .width(Length::Fill),
-            )
-            .padding(10)
-            .width(Length::Fill)
-            .style(theme::Container::Custom(Box::new(
-                CustomContainer::PlayerCard,
-            )))
-            .into()
+        let current_date_time = self
+            .arena
+            .as_ref()
+            .map_or("", |arena| arena.date_time.as_str());
+        let create_player_view = |player: &Player| -> Element<Message> {
+            let name_button = button(text(&player.name).size(16))
+                .style(theme::Button::Custom(Box::new(PlayerNameButton)))
+                .on_press(Message::ShowPlayerDetails(
+                    player.account_id,
+                    player.name.clone(),
+                ));
+            let name_row = match &player.clan {
+                Some(clan) => row![
+                    button(text(format!("[{}]", clan.tag)).size(16))
+                        .style(theme::Button::Custom(Box::new(ClanTagButton)))
+                        .on_press(Message::ClanTagClicked(clan.clan_id, clan.tag.clone()))
+                        .padding([5, 0, 5, 5]),
+                    name_button
+                ],
+                None => row![name_button],
+            };
+            let mut name_column = column![name_row].spacing(4).width(Length::FillPortion(1));
+            if player.status == StatsStatus::Stale {
+                name_column = name_column.push(styled_text_with_color("stale", GRAY_COLOR));
+            }
+            if let Some(encounters) =
+                self.encounters
+                    .previous(self.config.region, player.account_id, current_date_time)
+            {
+                let badge = container(styled_text_with_color(
+                    &format!("Seen {}x", encounters.count),
+                    DISCORD_BLUE,
+                ))
+                .padding([2, 6])
+                .style(theme::Container::Custom(Box::new(CustomContainer::Badge)));
+                name_column = name_column.push(
+                    tooltip(badge, encounters.describe(), tooltip::Position::Bottom)
+                        .padding(8)
+                        .style(theme::Container::Custom(Box::new(CustomContainer::Tooltip))),
+                );
+            }
+            let note = self.notes.get(self.config.region, player.account_id);
+            if player.status != StatsStatus::Bot && player.account_id > 0 {
+                let note_button = button(styled_text_with_color(
+                    if note.is_some() { "Note" } else { "+ Note" },
+                    if note.is_some() {
+                        NOTE_COLOR
+                    } else {
+                        GRAY_COLOR
+                    },
+                ))
+                .style(theme::Button::Custom(Box::new(PlayerNameButton)))
+                .on_press(Message::EditNote(player.account_id, player.name.clone()))
+                .padding(0);
+                name_column = match note.filter(|note| !note.text.is_empty()) {
+                    Some(note) => name_column.push(
+                        tooltip(note_button, &note.text, tooltip::Position::Bottom)
+                            .padding(8)
+                            .style(theme::Container::Custom(Box::new(CustomContainer::Tooltip))),
+                    ),
+                    None => name_column.push(note_button),
+                };
+            }
+            if let Some(note) = note.filter(|note| !note.tags.is_empty()) {
+                name_column = name_column.push(tag_chips(&note.tags));
+            }
+            let blacklisted = note.is_some_and(|note| note.blacklisted);
+            if blacklisted {
+                name_column = name_column.push(styled_text_with_color(
+                    "Blacklisted",
+                    BLACKLIST_BORDER_COLOR,
+                ));
+            }
+
+            let placeholder = |message: String, color: Color| -> Element<Message> {
+                styled_text_with_color(&message, color)
+                    .width(Length::FillPortion(4))
+                    .into()
+            };
+            let details = match &player.status {
+                StatsStatus::Loading => placeholder("Loading stats...".to_string(), GRAY_COLOR),
+                StatsStatus::Hidden => placeholder("Hidden profile".to_string(), GRAY_COLOR),
+                StatsStatus::NotFound => placeholder(
+                    format!("No account found on {}", self.config.region),
+                    GRAY_COLOR,
+                ),
+                StatsStatus::Bot => placeholder("Bot".to_string(), GRAY_COLOR),
+                StatsStatus::Failed(e) => {
+                    placeholder(format!("Stats unavailable: {}", e), RED_COLOR)
+                }
+                StatsStatus::Loaded | StatsStatus::Stale if player.account.is_none() => {
+                    placeholder("No random battles".to_string(), GRAY_COLOR)
+                }
+                StatsStatus::Loaded | StatsStatus::Stale => self.player_stats_view(player),
+            };
+
+            // Our own card and division mates keep their style, anyone else can be blacklisted
+            let card = match player.card {
+                CardKind::Friend | CardKind::Normal if blacklisted => CardKind::Blacklisted,
+                card => card,
+            };
+
+            container(row![name_column, details].spacing(20).width(Length::Fill))
+                .padding(10)
+                .width(Length::Fill)
+                .style(theme::Container::Custom(Box::new(
+                    CustomContainer::PlayerCard(card),
+                )))
+                .into()
         };
 
-        let player_content = row![
-            column(self.team1.iter().map(create_player_view).collect())
-                .spacing(5)
-                .width(Length::FillPortion(1)),
-            column(self.team2.iter().map(create_player_view).collect())
+        let team_column = |team: Team, players: &[Player], key: TeamSortKey| {
+            let sort_picker = row![
+                styled_text_with_color("Sort by", GRAY_COLOR),
+                pick_list(&TeamSortKey::ALL[..], Some(key), move |key| {
+                    Message::TeamSortSelected(team, key)
+                })
+                .style(theme::PickList::Custom(
+                    Rc::new(TopBarPickList),
+                    Rc::new(TopBarPickList)
+                ))
+                .padding(5)
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center);
+
+            team_sort::sorted_players(players, key)
+                .into_iter()
+                .fold(column![sort_picker], |column, player| {
+                    column.push(create_player_view(player))
+                })
                 .spacing(5)
                 .width(Length::FillPortion(1))
+        };
+
+        let comparison = team_comparison_view(
+            &team_summary::summarize(&self.team1),
+            &team_summary::summarize(&self.team2),
+        );
+        let player_content = column![
+            comparison,
+            row![
+                team_column(Team::Team1, &self.team1, self.config.team1_sort),
+                team_column(Team::Team2, &self.team2, self.config.team2_sort)
+            ]
+            .spacing(10)
         ]
         .spacing(10)
         .padding(20)
         .width(Length::Fill);
 
-        let scrollable_content = scrollable(player_content)
+        let content: Element<Message> = match (&self.player_details, self.view) {
+            (Some(details), _) => self.player_details_view(details),
+            (None, View::Teams) => player_content.into(),
+            (None, View::ReplayBrowser) => self.replay_browser_view(),
+            (None, View::Session) => self.session_view(),
+        };
+
+        let scrollable_content = scrollable(content)
             .width(Length::Fill)
             .height(Length::Fill)
             .style(theme::Scrollable::Custom(Box::new(CustomScrollable)));
 
+        // Only meaningful for a live or demo battle, not the other views
+        let win_chance = match self.view {
+            View::Teams => self.config.win_model.win_chance(&self.team1, &self.team2),
+            View::ReplayBrowser | View::Session => None,
+        }
+        .map(|chance| {
+            let color = if chance >= 50.0 {
+                LIGHT_GREEN_COLOR
+            } else {
+                LIGHT_RED_COLOR
+            };
+            (format!("{:.0}%", chance), color)
+        });
+
         let top_bar = container(
             row![container(
                 row![
+                    stat_row("Win chance: ", win_chance),
+                    pick_list(
+                        &StatsWindow::ALL[..],
+                        Some(self.config.stats_window),
+                        Message::StatsWindowSelected
+                    )
+                    .style(theme::PickList::Custom(
+                        Rc::new(TopBarPickList),
+                        Rc::new(TopBarPickList)
+                    ))
+                    .padding(8),
+                    pick_list(
+                        &Region::ALL[..],
+                        Some(self.config.region),
+                        Message::RegionSelected
+                    )
+                    .style(theme::PickList::Custom(
+                        Rc::new(TopBarPickList),
+                        Rc::new(TopBarPickList)
+                    ))
+                    .padding(8),
                     Icon::Home.button(Message::OpenGithub, &self.config),
                     Icon::Folder.button(Message::OpenFolderDialog, &self.config),
+                    Icon::History.button(
+                        match self.view {
+                            View::ReplayBrowser => Message::ShowTeams,
+                            _ => Message::ShowReplayBrowser,
+                        },
+                        &self.config
+                    ),
+                    Icon::Session.button(
+                        match self.view {
+                            View::Session => Message::ShowTeams,
+                            _ => Message::ShowSession,
+                        },
+                        &self.config
+                    ),
                 ]
                 .spacing(8)
+                .align_items(iced::Alignment::Center)
             )
             .width(Length::Fill)
             .align_x(iced::alignment::Horizontal::Right)]
@@ -660,7 +2134,7 @@ impl Application for StatsViewer {
         .width(Length::Fill)
         .height(Length::Fixed(44.0));
 
-        container(
+        let app = container(
             column![top_bar, container(scrollable_content)]
                 .spacing(0)
                 .height(Length::Fill),
@@ -669,9 +2143,167 @@ impl Application for StatsViewer {
         .height(Length::Fill)
         .style(theme::Container::Custom(Box::new(
             CustomContainer::Background,
+        )));
+
+        match &self.note_draft {
+            Some((_, draft)) => Modal::new(app, self.note_editor(draft))
+                .on_blur(Message::CancelNote)
+                .into(),
+            None => app.into(),
+        }
+    }
+}
+
+// A "Label: value" line, missing values are drawn as a gray dash
+fn stat_row<'a>(label: &str, value: Option<(String, Color)>) -> Row<'a, Message> {
+    let (value, color) = value.unwrap_or_else(|| ("-".to_string(), GRAY_COLOR));
+    row![styled_text(label), styled_text_with_color(&value, color)]
+}
+
+fn tag_chips<'a>(tags: &[String]) -> Row<'a, Message> {
+    tags.iter().fold(row![].spacing(4), |chips, tag| {
+        chips.push(
+            container(styled_text_with_color(tag, NOTE_COLOR))
+                .padding([1, 6])
+                .style(theme::Container::Custom(Box::new(CustomContainer::TagChip))),
+        )
+    })
+}
+
+// One row of the team comparison: both values and the allies' lead over the enemies
+fn comparison_row<'a>(
+    label: &str,
+    values: [Option<(String, Color)>; 2],
+    lead: Option<(String, Color)>,
+) -> Row<'a, Message> {
+    let [team1, team2] = values.map(|value| {
+        let (value, color) = value.unwrap_or_else(|| ("-".to_string(), GRAY_COLOR));
+        styled_text_with_color(&value, color).width(Length::FillPortion(2))
+    });
+    let (lead, lead_color) = lead.unwrap_or_else(|| ("-".to_string(), GRAY_COLOR));
+    row![
+        styled_text_with_color(label, GRAY_COLOR).width(Length::FillPortion(2)),
+        team1,
+        team2,
+        styled_text_with_color(&lead, lead_color).width(Length::FillPortion(1)),
+    ]
+    .spacing(10)
+}
+
+// Difference of team 1 over team 2, green when team 1 is ahead
+fn lead(team1: Option<f32>, team2: Option<f32>, decimals: usize) -> Option<(String, Color)> {
+    let lead = team1? - team2?;
+    let color = if lead > 0.0 {
+        LIGHT_GREEN_COLOR
+    } else if lead < 0.0 {
+        LIGHT_RED_COLOR
+    } else {
+        GRAY_COLOR
+    };
+    Some((format!("{:+.*}", decimals, lead), color))
+}
+
+fn team_comparison_view<'a>(team1: &TeamSummary, team2: &TeamSummary) -> Element<'a, Message> {
+    let pr = |pr: Option<f32>| pr.map(|pr| (format!("{:.0}", pr), Rating::from_pr(pr).color()));
+    let winrate = |winrate: Option<f32>| {
+        winrate.map(|winrate| {
+            (
+                format!("{:.1}%", winrate),
+                Rating::from_winrate(winrate).color(),
+            )
+        })
+    };
+    let count = |count: f32, color: Color| Some((format!("{:.0}", count), color));
+    let pr_row = |label: &str, a: Option<f32>, b: Option<f32>| {
+        comparison_row(label, [pr(a), pr(b)], lead(a, b, 0))
+    };
+    let winrate_row = |label: &str, a: Option<f32>, b: Option<f32>| {
+        comparison_row(label, [winrate(a), winrate(b)], lead(a, b, 1))
+    };
+    let count_row = |label: &str, a: f32, b: f32, color: Color| {
+        comparison_row(
+            label,
+            [count(a, color), count(b, color)],
+            lead(Some(a), Some(b), 0),
+        )
+    };
+
+    let rows = column![
+        comparison_row(
+            "",
+            [
+                Some(("Team 1".to_string(), TEXT_COLOR)),
+                Some(("Team 2".to_string(), TEXT_COLOR)),
+            ],
+            Some(("Lead".to_string(), TEXT_COLOR)),
+        ),
+        pr_row("Avg PR", team1.avg_pr, team2.avg_pr),
+        pr_row("Weighted PR", team1.weighted_pr, team2.weighted_pr),
+        winrate_row(
+            "Avg Acc WR",
+            team1.avg_account_winrate,
+            team2.avg_account_winrate
+        ),
+        winrate_row(
+            "Avg Ship WR",
+            team1.avg_ship_winrate,
+            team2.avg_ship_winrate
+        ),
+        count_row(
+            "Battles",
+            team1.total_battles as f32,
+            team2.total_battles as f32,
+            TEXT_COLOR
+        ),
+        count_row(
+            "Unicums",
+            team1.unicums as f32,
+            team2.unicums as f32,
+            RATING_UNICUM_COLOR
+        ),
+    ]
+    .spacing(4);
+
+    // Per class rows, for every class either side brought
+    let class_rows = ShipClass::ALL.iter().fold(rows, |rows, &class| {
+        let find = |summary: &TeamSummary| {
+            summary
+                .classes
+                .iter()
+                .find(|classes| classes.class == class)
+                .copied()
+        };
+        let (a, b) = (find(team1), find(team2));
+        if a.is_none() && b.is_none() {
+            return rows;
+        }
+        let short = class.short();
+        let count = |summary: Option<ClassSummary>| summary.map_or(0, |summary| summary.count);
+        rows.push(count_row(
+            short,
+            count(a) as f32,
+            count(b) as f32,
+            TEXT_COLOR,
+        ))
+        .push(pr_row(
+            &format!("{} PR", short),
+            a.and_then(|a| a.avg_pr),
+            b.and_then(|b| b.avg_pr),
+        ))
+        .push(winrate_row(
+            &format!("{} WR", short),
+            a.and_then(|a| a.avg_winrate),
+            b.and_then(|b| b.avg_winrate),
+        ))
+    });
+
+    container(class_rows.width(Length::Fill))
+        .padding(10)
+        .width(Length::Fill)
+        .style(theme::Container::Custom(Box::new(
+            CustomContainer::TeamSummary,
         )))
         .into()
-    }
 }
 
 // Discord-like theme implementation
@@ -679,7 +2311,11 @@ impl Application for StatsViewer {
 pub enum CustomContainer {
     Background,
     TopBar,
-    PlayerCard,
+    PlayerCard(CardKind),
+    TeamSummary,
+    Badge,
+    Tooltip,
+    TagChip,
 }
 
 impl container::StyleSheet for CustomContainer {
@@ -698,10 +2334,47 @@ impl container::StyleSheet for CustomContainer {
                 background: Some(iced::Background::Color(DISCORD_BACKGROUND)),
                 ..Default::default()
             },
-            CustomContainer::PlayerCard => container::Appearance {
-                background: Some(iced::Background::Color(DISCORD_CARD)),
+            CustomContainer::PlayerCard(card) => {
+                let (background, border_color) = match card {
+                    CardKind::Own => (OWN_CARD_COLOR, OWN_BORDER_COLOR),
+                    CardKind::DivisionMate => (DISCORD_CARD, DIVISION_BORDER_COLOR),
+                    CardKind::Friend => (DISCORD_CARD, FRIEND_BORDER_COLOR),
+                    CardKind::Blacklisted => (BLACKLIST_CARD_COLOR, BLACKLIST_BORDER_COLOR),
+                    CardKind::Normal => (DISCORD_CARD, Color::TRANSPARENT),
+                };
+                container::Appearance {
+                    background: Some(iced::Background::Color(background)),
+                    border_radius: 8.0.into(),
+                    border_width: if *card == CardKind::Normal { 0.0 } else { 2.0 },
+                    border_color,
+                    ..Default::default()
+                }
+            }
+            CustomContainer::Badge => container::Appearance {
+                background: Some(iced::Background::Color(DISCORD_TOP_BAR)),
+                border_radius: 4.0.into(),
+                border_width: 1.0,
+                border_color: DISCORD_BLUE,
+                ..Default::default()
+            },
+            CustomContainer::TagChip => container::Appearance {
+                background: Some(iced::Background::Color(DISCORD_TOP_BAR)),
                 border_radius: 8.0.into(),
-                border_width: 0.0,
+                border_width: 1.0,
+                border_color: NOTE_COLOR,
+                ..Default::default()
+            },
+            CustomContainer::Tooltip => container::Appearance {
+                background: Some(iced::Background::Color(DISCORD_BLACK)),
+                text_color: Some(TEXT_COLOR),
+                border_radius: 4.0.into(),
+                ..Default::default()
+            },
+            CustomContainer::TeamSummary => container::Appearance {
+                background: Some(iced::Background::Color(DISCORD_TOP_BAR)),
+                border_radius: 8.0.into(),
+                border_width: 1.0,
+                border_color: DISCORD_CARD_HOVERED,
                 ..Default::default()
             },
         }
@@ -770,6 +2443,7 @@ fn main() -> iced::Result {
     let mut settings = Settings::default();
     settings.window.size = (config.window_width, config.window_height);
     settings.window.resizable = true;
+    settings.flags = std::env::args().any(|arg| arg == "--demo");
 
     // Load and set the icon
     if let Ok(icon) = image_rs::open("assets/icon.png") {
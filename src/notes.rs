@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::region::Region;
+
+pub const NOTES_FILE: &str = "player_notes.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerNote {
+    // Name when the note was written, accounts can be renamed
+    pub name: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Players we never want to play with again, their card stands out in red
+    #[serde(default)]
+    pub blacklisted: bool,
+}
+
+impl PlayerNote {
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.tags.is_empty() && !self.blacklisted
+    }
+
+    pub fn toggle_tag(&mut self, tag: &str) {
+        match self.tags.iter().position(|existing| existing == tag) {
+            Some(index) => {
+                self.tags.remove(index);
+            }
+            None => self.tags.push(tag.to_string()),
+        }
+    }
+}
+
+// Our own annotations on other players, keyed by region and account id
+pub struct NotesStore {
+    path: PathBuf,
+    notes: HashMap<String, PlayerNote>,
+}
+
+impl NotesStore {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(NOTES_FILE)
+    }
+
+    pub fn load(path: &Path) -> Self {
+        info!("Loading player notes from: {:?}", path);
+
+        let notes = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("Error parsing player notes: {}", e);
+                HashMap::new()
+            }),
+            Err(e) => {
+                warn!("No player notes found: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            notes,
+        }
+    }
+
+    fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(&self.notes) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(&self.path, contents) {
+                    error!("Error saving player notes: {}", e);
+                }
+            }
+            Err(e) => error!("Error serializing player notes: {}", e),
+        }
+    }
+
+    fn key(region: Region, account_id: i64) -> String {
+        format!("{}:{}", region.code(), account_id)
+    }
+
+    pub fn get(&self, region: Region, account_id: i64) -> Option<&PlayerNote> {
+        self.notes.get(&Self::key(region, account_id))
+    }
+
+    // Clearing the text, the tags and the blacklist flag deletes the note
+    pub fn set(&mut self, region: Region, account_id: i64, note: PlayerNote) {
+        let key = Self::key(region, account_id);
+        if note.is_empty() {
+            self.notes.remove(&key);
+        } else {
+            self.notes.insert(key, note);
+        }
+        self.save();
+    }
+}
@@ -0,0 +1,79 @@
+use std::cmp::Reverse;
+
+use crate::ships::{ShipClass, ShipDatabase};
+use crate::stats::{BattleStats, StatsError};
+
+// Ships listed in the detail pane, most played first
+const TOP_SHIPS: usize = 10;
+
+// Everything shown in the player detail pane
+#[derive(Debug, Clone)]
+pub struct PlayerDetails {
+    pub account_id: i64,
+    pub name: String,
+    // None while the lookup is running
+    pub ships: Option<Result<Vec<(u64, BattleStats)>, StatsError>>,
+    // Shown instead of the ships when there is nothing to look up
+    pub placeholder: Option<&'static str>,
+    // Account winrate at every battle we recorded the player in, oldest first
+    pub trend: Vec<f32>,
+}
+
+impl PlayerDetails {
+    pub fn new(account_id: i64, name: String, trend: Vec<f32>) -> Self {
+        Self {
+            account_id,
+            name,
+            ships: None,
+            placeholder: None,
+            trend,
+        }
+    }
+
+    pub fn unavailable(self, placeholder: &'static str) -> Self {
+        Self {
+            placeholder: Some(placeholder),
+            ..self
+        }
+    }
+}
+
+pub fn top_ships(ships: &[(u64, BattleStats)]) -> Vec<(u64, BattleStats)> {
+    let mut top = ships.to_vec();
+    top.sort_by_key(|(_, stats)| Reverse(stats.battles));
+    top.truncate(TOP_SHIPS);
+    top
+}
+
+// Totals per class in lobby order, ships missing from the database are skipped
+pub fn class_totals(
+    ships: &[(u64, BattleStats)],
+    database: &ShipDatabase,
+) -> Vec<(ShipClass, BattleStats)> {
+    ShipClass::ALL
+        .iter()
+        .map(|&class| {
+            let mut total = BattleStats::default();
+            ships
+                .iter()
+                .filter(|(ship_id, _)| database.get(*ship_id).map(|info| info.class) == Some(class))
+                .for_each(|(_, stats)| total.merge(stats));
+            (class, total)
+        })
+        .filter(|(_, total)| total.battles > 0)
+        .collect()
+}
+
+// Battles played at every tier, index 0 is tier I
+pub fn tier_battles(ships: &[(u64, BattleStats)], database: &ShipDatabase) -> [u32; 11] {
+    let mut tiers = [0; 11];
+    for (ship_id, stats) in ships {
+        let Some(info) = database.get(*ship_id) else {
+            continue;
+        };
+        if let Some(battles) = tiers.get_mut((info.tier as usize).wrapping_sub(1)) {
+            *battles += stats.battles;
+        }
+    }
+    tiers
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::stats::BattleStats;
+
+// Expected values in the wows-numbers.com expected.json format
+pub const EXPECTED_VALUES_FILE: &str = "expected_values.json";
+
+// Average a player of the ship is expected to reach
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ExpectedShipValues {
+    #[serde(rename = "average_damage_dealt")]
+    pub damage: f32,
+    #[serde(rename = "average_frags")]
+    pub frags: f32,
+    // Percentage, e.g. 51.3
+    #[serde(rename = "win_rate")]
+    pub winrate: f32,
+}
+
+// Ships without enough data are listed as an empty array
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExpectedEntry {
+    Values(ExpectedShipValues),
+    Missing(serde::de::IgnoredAny),
+}
+
+#[derive(Deserialize)]
+struct ExpectedFile {
+    data: HashMap<String, ExpectedEntry>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedValues {
+    ships: HashMap<u64, ExpectedShipValues>,
+}
+
+impl ExpectedValues {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(EXPECTED_VALUES_FILE)
+    }
+
+    // Missing or broken files give an empty table, PR is then unavailable
+    pub fn load(path: &Path) -> Self {
+        info!("Loading expected values from: {:?}", path);
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("No expected values available: {}", e);
+                return Self::default();
+            }
+        };
+
+        match Self::parse(&contents) {
+            Ok(values) => {
+                info!("Loaded expected values for {} ships", values.ships.len());
+                values
+            }
+            Err(e) => {
+                error!("Error parsing expected values: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, serde_json::Error> {
+        let file: ExpectedFile = serde_json::from_str(contents)?;
+        let ships = file
+            .data
+            .into_iter()
+            .filter_map(|(ship_id, entry)| match (ship_id.parse(), entry) {
+                (Ok(ship_id), ExpectedEntry::Values(values)) => Some((ship_id, values)),
+                _ => None,
+            })
+            .collect();
+        Ok(Self { ships })
+    }
+
+    pub fn get(&self, ship_id: u64) -> Option<&ExpectedShipValues> {
+        self.ships.get(&ship_id)
+    }
+
+    pub fn ship_pr(&self, ship_id: u64, stats: &BattleStats) -> Option<f32> {
+        self.account_pr(&[(ship_id, *stats)])
+    }
+
+    // Battle-weighted PR over several ships, ships without expected values are skipped
+    pub fn account_pr(&self, ships: &[(u64, BattleStats)]) -> Option<f32> {
+        let mut actual = Totals::default();
+        let mut expected = Totals::default();
+        for (ship_id, stats) in ships {
+            let Some(values) = self.get(*ship_id) else {
+                continue;
+            };
+            let battles = stats.battles as f32;
+            actual.add(
+                stats.damage_dealt as f32,
+                stats.frags as f32,
+                stats.wins as f32,
+            );
+            expected.add(
+                values.damage * battles,
+                values.frags * battles,
+                values.winrate / 100.0 * battles,
+            );
+        }
+
+        if expected.damage <= 0.0 || expected.frags <= 0.0 || expected.wins <= 0.0 {
+            return None;
+        }
+        Some(personal_rating(
+            actual.damage / expected.damage,
+            actual.frags / expected.frags,
+            actual.wins / expected.wins,
+        ))
+    }
+}
+
+#[derive(Default)]
+struct Totals {
+    damage: f32,
+    frags: f32,
+    wins: f32,
+}
+
+impl Totals {
+    fn add(&mut self, damage: f32, frags: f32, wins: f32) {
+        self.damage += damage;
+        self.frags += frags;
+        self.wins += wins;
+    }
+}
+
+// The wows-numbers formula: ratios are normalized so that a poor player scores 0
+// and an average one scores 1, then weighted 700/300/150.
+pub fn personal_rating(damage_ratio: f32, frags_ratio: f32, wins_ratio: f32) -> f32 {
+    let damage = ((damage_ratio - 0.4) / (1.0 - 0.4)).max(0.0);
+    let frags = ((frags_ratio - 0.1) / (1.0 - 0.1)).max(0.0);
+    let wins = ((wins_ratio - 0.7) / (1.0 - 0.7)).max(0.0);
+    700.0 * damage + 300.0 * frags + 150.0 * wins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPECTED: &str = r#"{"time": 1718000000, "data": {
+        "1": {"average_damage_dealt": 50000, "average_frags": 1.0, "win_rate": 50.0},
+        "2": {"average_damage_dealt": 20000, "average_frags": 0.5, "win_rate": 40.0},
+        "3": []
+    }}"#;
+
+    fn stats(battles: u32, wins: u32, damage_dealt: u64, frags: u32) -> BattleStats {
+        BattleStats {
+            battles,
+            wins,
+            damage_dealt,
+            frags,
+        }
+    }
+
+    #[test]
+    fn expected_performance_scores_the_full_weights() {
+        // 700 + 300 + 150: playing exactly at the expected values is 1150, not 1000
+        assert_eq!(personal_rating(1.0, 1.0, 1.0), 1150.0);
+
+        let values = ExpectedValues::parse(EXPECTED).unwrap();
+        let pr = values.ship_pr(1, &stats(100, 50, 5_000_000, 100)).unwrap();
+        assert!((pr - 1150.0).abs() < 0.5, "{}", pr);
+    }
+
+    #[test]
+    fn poor_ratios_are_clamped_at_zero() {
+        assert_eq!(personal_rating(0.1, 0.0, 0.5), 0.0);
+
+        let values = ExpectedValues::parse(EXPECTED).unwrap();
+        assert_eq!(values.ship_pr(1, &stats(10, 0, 0, 0)), Some(0.0));
+    }
+
+    #[test]
+    fn account_pr_weights_ships_by_battles() {
+        let values = ExpectedValues::parse(EXPECTED).unwrap();
+        // Twice the expected damage on ship 1, expected values on ship 2
+        let ships = [
+            (1, stats(10, 5, 1_000_000, 10)),
+            (2, stats(30, 12, 600_000, 15)),
+        ];
+        // Damage ratio 1.6M / 1.1M, frags and wins exactly as expected
+        let damage = (1_600_000.0 / 1_100_000.0 - 0.4) / 0.6;
+        let expected = 700.0 * damage + 300.0 + 150.0;
+        let pr = values.account_pr(&ships).unwrap();
+        assert!((pr - expected).abs() < 0.5, "{} vs {}", pr, expected);
+    }
+
+    #[test]
+    fn ships_without_expected_values_have_no_pr() {
+        let values = ExpectedValues::parse(EXPECTED).unwrap();
+        assert!(values.get(3).is_none());
+        assert_eq!(values.ship_pr(3, &stats(10, 5, 100_000, 5)), None);
+        assert_eq!(values.ship_pr(4, &stats(10, 5, 100_000, 5)), None);
+        // Unknown ships are left out of the account PR instead of spoiling it
+        let with_unknown = [(1, stats(100, 50, 5_000_000, 100)), (4, stats(50, 0, 0, 0))];
+        let pr = values.account_pr(&with_unknown).unwrap();
+        assert!((pr - 1150.0).abs() < 0.5, "{}", pr);
+    }
+}
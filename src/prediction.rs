@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Player;
+
+// Battles of imaginary 50% games mixed into every winrate, so a player on
+// 60% after 10 battles counts as far less convincing than one after 1000
+const ACCOUNT_PRIOR_BATTLES: f32 = 50.0;
+const SHIP_PRIOR_BATTLES: f32 = 20.0;
+
+// How much each signal contributes to a player's skill estimate
+const ACCOUNT_WEIGHT: f32 = 0.5;
+const SHIP_WEIGHT: f32 = 0.3;
+const PR_WEIGHT: f32 = 0.2;
+
+// Calibration needs a reasonable number of battles to beat the defaults
+const MIN_CALIBRATION_SAMPLES: usize = 20;
+
+// Win chance model: every player gets a skill estimate in winrate percent,
+// a team's strength is the mean over its players, and the difference between
+// the two strengths goes through a logistic curve:
+//
+//     P(team1 wins) = 1 / (1 + exp(-(scale * (strength1 - strength2) + bias)))
+//
+// `scale` and `bias` can be fitted against our own battle results with `calibrate`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WinModel {
+    pub scale: f32,
+    pub bias: f32,
+}
+
+impl Default for WinModel {
+    // Roughly +6% win chance per point of average winrate advantage
+    fn default() -> Self {
+        Self {
+            scale: 0.25,
+            bias: 0.0,
+        }
+    }
+}
+
+impl WinModel {
+    // Chance in percent that team1 wins, None while nobody has stats
+    pub fn win_chance(&self, team1: &[Player], team2: &[Player]) -> Option<f32> {
+        let strength1 = team_strength(team1)?;
+        let strength2 = team_strength(team2)?;
+        Some(self.probability(strength1 - strength2) * 100.0)
+    }
+
+    fn probability(&self, strength_difference: f32) -> f32 {
+        1.0 / (1.0 + (-(self.scale * strength_difference + self.bias)).exp())
+    }
+
+    // Fit `scale` and `bias` by gradient descent on the log loss, samples are the
+    // strength difference at battle start and whether team1 won
+    pub fn calibrate(samples: &[(f32, bool)]) -> Option<Self> {
+        if samples.len() < MIN_CALIBRATION_SAMPLES {
+            return None;
+        }
+
+        let mut model = Self::default();
+        let learning_rate = 0.05;
+        for _ in 0..2000 {
+            let (mut scale_gradient, mut bias_gradient) = (0.0, 0.0);
+            for &(difference, won) in samples {
+                let error = model.probability(difference) - if won { 1.0 } else { 0.0 };
+                scale_gradient += error * difference;
+                bias_gradient += error;
+            }
+            let count = samples.len() as f32;
+            model.scale -= learning_rate * scale_gradient / count;
+            model.bias -= learning_rate * bias_gradient / count;
+        }
+        Some(model)
+    }
+}
+
+// Mean skill of the players we have stats for, None when there are none
+pub fn team_strength(players: &[Player]) -> Option<f32> {
+    let skills: Vec<f32> = players.iter().filter_map(player_skill).collect();
+    if skills.is_empty() {
+        return None;
+    }
+    Some(skills.iter().sum::<f32>() / skills.len() as f32)
+}
+
+// Weighted blend of the available signals, expressed as an expected winrate
+fn player_skill(player: &Player) -> Option<f32> {
+    let account = player
+        .account
+        .map(|account| shrink(account.winrate, account.battles, ACCOUNT_PRIOR_BATTLES));
+    let ship = player
+        .ship
+        .map(|ship| shrink(ship.winrate, ship.battles, SHIP_PRIOR_BATTLES));
+    let pr = player.ship.and_then(|ship| ship.pr).map(pr_to_winrate);
+
+    let (sum, weight) = [
+        (account, ACCOUNT_WEIGHT),
+        (ship, SHIP_WEIGHT),
+        (pr, PR_WEIGHT),
+    ]
+    .into_iter()
+    .filter_map(|(value, weight)| Some((value? * weight, weight)))
+    .fold((0.0, 0.0), |(sum, total), (value, weight)| {
+        (sum + value, total + weight)
+    });
+    (weight > 0.0).then(|| sum / weight)
+}
+
+fn shrink(winrate: f32, battles: u32, prior_battles: f32) -> f32 {
+    let battles = battles as f32;
+    (winrate * battles + 50.0 * prior_battles) / (battles + prior_battles)
+}
+
+// Rough fit of the community tiers: average PR (~1100) plays around 51%,
+// unicums (~2100) around 61%
+fn pr_to_winrate(pr: u32) -> f32 {
+    50.0 + (pr as f32 - 1000.0) / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ships::ShipClass;
+
+    fn team(stats: &[(u32, f32, u32)]) -> Vec<Player> {
+        stats
+            .iter()
+            .map(|&stats| Player::sample(ShipClass::Cruiser, Some(stats)))
+            .collect()
+    }
+
+    #[test]
+    fn equal_teams_are_even() {
+        let team1 = team(&[(200, 55.0, 1500), (50, 48.0, 900)]);
+        let team2 = team(&[(50, 48.0, 900), (200, 55.0, 1500)]);
+        let chance = WinModel::default().win_chance(&team1, &team2).unwrap();
+        assert!((chance - 50.0).abs() < 0.01, "{}", chance);
+    }
+
+    #[test]
+    fn stronger_team_is_favoured() {
+        let strong = team(&[(500, 60.0, 2000), (500, 58.0, 1800)]);
+        let weak = team(&[(500, 46.0, 700), (500, 48.0, 900)]);
+        let model = WinModel::default();
+        assert!(model.win_chance(&strong, &weak).unwrap() > 50.0);
+        assert!(model.win_chance(&weak, &strong).unwrap() < 50.0);
+    }
+
+    #[test]
+    fn no_stats_gives_no_chance() {
+        let hidden = vec![Player::sample(ShipClass::Cruiser, None)];
+        let known = team(&[(100, 50.0, 1000)]);
+        assert_eq!(WinModel::default().win_chance(&hidden, &known), None);
+    }
+
+    #[test]
+    fn calibration_moves_towards_the_data() {
+        // Battles from a world where strength matters more than the default assumes
+        // and team1 has a small edge, with win counts matching the true odds
+        let truth = WinModel {
+            scale: 0.6,
+            bias: 0.3,
+        };
+        let mut samples = Vec::new();
+        for step in -8..=8 {
+            let difference = step as f32 * 0.5;
+            let wins = (truth.probability(difference) * 20.0).round() as usize;
+            samples.extend((0..20).map(|battle| (difference, battle < wins)));
+        }
+
+        let model = WinModel::calibrate(&samples).unwrap();
+        let default = WinModel::default();
+        assert!((model.scale - truth.scale).abs() < (default.scale - truth.scale).abs());
+        assert!((model.bias - truth.bias).abs() < (default.bias - truth.bias).abs());
+    }
+
+    #[test]
+    fn calibration_needs_enough_battles() {
+        let samples = vec![(1.0, true); MIN_CALIBRATION_SAMPLES - 1];
+        assert_eq!(WinModel::calibrate(&samples), None);
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use crate::region::Region;
+
+// Site the player name and clan tag buttons link to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ProfileSite {
+    #[default]
+    WowsNumbers,
+    Official,
+    // Uses `Config::custom_profile_url` and `Config::custom_clan_url`
+    Custom,
+}
+
+impl ProfileSite {
+    fn player_template(self, region: Region) -> &'static str {
+        match (self, region) {
+            (ProfileSite::WowsNumbers, Region::Eu) => {
+                "https://wows-numbers.com/player/{account_id},{name}/"
+            }
+            (ProfileSite::WowsNumbers, Region::Na) => {
+                "https://na.wows-numbers.com/player/{account_id},{name}/"
+            }
+            (ProfileSite::WowsNumbers, Region::Asia) => {
+                "https://asia.wows-numbers.com/player/{account_id},{name}/"
+            }
+            (ProfileSite::Official, Region::Eu) => {
+                "https://profile.worldofwarships.eu/statistics/{account_id}"
+            }
+            (ProfileSite::Official, Region::Na) => {
+                "https://profile.worldofwarships.com/statistics/{account_id}"
+            }
+            (ProfileSite::Official, Region::Asia) => {
+                "https://profile.worldofwarships.asia/statistics/{account_id}"
+            }
+            (ProfileSite::Custom, _) => "",
+        }
+    }
+
+    fn clan_template(self, region: Region) -> &'static str {
+        match (self, region) {
+            (ProfileSite::WowsNumbers, Region::Eu) => {
+                "https://wows-numbers.com/clan/{clan_id},{tag}/"
+            }
+            (ProfileSite::WowsNumbers, Region::Na) => {
+                "https://na.wows-numbers.com/clan/{clan_id},{tag}/"
+            }
+            (ProfileSite::WowsNumbers, Region::Asia) => {
+                "https://asia.wows-numbers.com/clan/{clan_id},{tag}/"
+            }
+            (ProfileSite::Official, Region::Eu) => {
+                "https://clans.worldofwarships.eu/clans/gateway/wows/profile/{clan_id}"
+            }
+            (ProfileSite::Official, Region::Na) => {
+                "https://clans.worldofwarships.com/clans/gateway/wows/profile/{clan_id}"
+            }
+            (ProfileSite::Official, Region::Asia) => {
+                "https://clans.worldofwarships.asia/clans/gateway/wows/profile/{clan_id}"
+            }
+            (ProfileSite::Custom, _) => "",
+        }
+    }
+}
+
+// Fill in `{region}`, `{account_id}` and `{name}` for the chosen site
+pub fn profile_url(
+    site: ProfileSite,
+    custom_template: &str,
+    region: Region,
+    account_id: i64,
+    name: &str,
+) -> String {
+    let template = match site {
+        ProfileSite::Custom => custom_template,
+        _ => site.player_template(region),
+    };
+    template
+        .replace("{region}", region.code())
+        .replace("{account_id}", &account_id.to_string())
+        .replace("{name}", name)
+}
+
+// Fill in `{region}`, `{clan_id}` and `{tag}` for the chosen site
+pub fn clan_url(
+    site: ProfileSite,
+    custom_template: &str,
+    region: Region,
+    clan_id: i64,
+    tag: &str,
+) -> String {
+    let template = match site {
+        ProfileSite::Custom => custom_template,
+        _ => site.clan_template(region),
+    };
+    template
+        .replace("{region}", region.code())
+        .replace("{clan_id}", &clan_id.to_string())
+        .replace("{tag}", tag)
+}
@@ -0,0 +1,62 @@
+use iced::Color;
+
+use crate::colors::*;
+
+// Community-standard performance tiers shared by every stat column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
+    Bad,
+    BelowAverage,
+    Average,
+    Good,
+    VeryGood,
+    Great,
+    Unicum,
+    SuperUnicum,
+}
+
+impl Rating {
+    const ALL: [Rating; 8] = [
+        Rating::Bad,
+        Rating::BelowAverage,
+        Rating::Average,
+        Rating::Good,
+        Rating::VeryGood,
+        Rating::Great,
+        Rating::Unicum,
+        Rating::SuperUnicum,
+    ];
+
+    // `thresholds` holds the lower bound of every tier above Bad
+    fn from_thresholds(value: f32, thresholds: [f32; 7]) -> Self {
+        let tier = thresholds.iter().filter(|&&lower| value >= lower).count();
+        Self::ALL[tier]
+    }
+
+    pub fn from_pr(pr: f32) -> Self {
+        Self::from_thresholds(pr, [750.0, 1100.0, 1350.0, 1550.0, 1750.0, 2100.0, 2450.0])
+    }
+
+    // Winrate in percent
+    pub fn from_winrate(winrate: f32) -> Self {
+        Self::from_thresholds(winrate, [47.0, 49.0, 52.0, 54.0, 56.0, 60.0, 65.0])
+    }
+
+    // Actual over expected, used for both damage and frags
+    pub fn from_ratio(ratio: f32) -> Self {
+        Self::from_thresholds(ratio, [0.8, 0.95, 1.1, 1.2, 1.4, 1.7, 2.3])
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Rating::Bad => RATING_BAD_COLOR,
+            Rating::BelowAverage => RATING_BELOW_AVERAGE_COLOR,
+            Rating::Average => RATING_AVERAGE_COLOR,
+            Rating::Good => RATING_GOOD_COLOR,
+            Rating::VeryGood => RATING_VERY_GOOD_COLOR,
+            Rating::Great => RATING_GREAT_COLOR,
+            Rating::Unicum => RATING_UNICUM_COLOR,
+            Rating::SuperUnicum => RATING_SUPER_UNICUM_COLOR,
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+// Game server a player account lives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Region {
+    #[default]
+    Eu,
+    Na,
+    Asia,
+}
+
+impl Region {
+    pub const ALL: [Region; 3] = [Region::Eu, Region::Na, Region::Asia];
+
+    pub fn api_host(self) -> &'static str {
+        match self {
+            Region::Eu => "https://api.worldofwarships.eu",
+            Region::Na => "https://api.worldofwarships.com",
+            Region::Asia => "https://api.worldofwarships.asia",
+        }
+    }
+
+    // Lowercase code used in profile link templates
+    pub fn code(self) -> &'static str {
+        match self {
+            Region::Eu => "eu",
+            Region::Na => "na",
+            Region::Asia => "asia",
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Region::Eu => "EU",
+            Region::Na => "NA",
+            Region::Asia => "ASIA",
+        };
+        write!(f, "{}", label)
+    }
+}
@@ -0,0 +1,458 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use blowfish::cipher::generic_array::GenericArray;
+use blowfish::cipher::{BlockDecrypt, KeyInit};
+use blowfish::Blowfish;
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::arena::{ArenaInfo, Relation};
+
+pub const REPLAY_EXTENSION: &str = "wowsreplay";
+
+// First four bytes of every .wowsreplay file
+const REPLAY_MAGIC: u32 = 0x1134_3212;
+
+// JSON blocks are a few hundred KB at most, anything bigger is garbage
+const MAX_BLOCK_SIZE: u32 = 16 * 1024 * 1024;
+
+// Key the client uses to encrypt the packet stream
+const BLOWFISH_KEY: [u8; 16] = [
+    0x29, 0xB7, 0xC9, 0x09, 0x38, 0x3F, 0x84, 0x88, 0xFA, 0x98, 0xEC, 0x4E, 0x13, 0x19, 0x79, 0xFB,
+];
+
+// Every packet starts with payload size, type and timestamp
+const PACKET_HEADER_SIZE: usize = 12;
+
+// Sent once at the end of the battle
+const BATTLE_RESULTS_PACKET: u32 = 0x22;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    BadMagic(u32),
+    Truncated,
+    NoBlocks,
+    BlockTooLarge(u32),
+    Json(serde_json::Error),
+    Decompress(io::Error),
+    ResultsLayout,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "failed to read replay: {}", e),
+            ReplayError::BadMagic(magic) => write!(f, "not a replay file (magic {:#010x})", magic),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+            ReplayError::NoBlocks => write!(f, "replay has no JSON blocks"),
+            ReplayError::BlockTooLarge(size) => {
+                write!(f, "JSON block of {} bytes is too large", size)
+            }
+            ReplayError::Json(e) => write!(f, "invalid JSON block: {}", e),
+            ReplayError::Decompress(e) => write!(f, "failed to inflate packet stream: {}", e),
+            ReplayError::ResultsLayout => {
+                write!(
+                    f,
+                    "battle results use the positional list layout, which is not supported"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            ReplayError::Truncated
+        } else {
+            ReplayError::Io(e)
+        }
+    }
+}
+
+impl From<serde_json::Error> for ReplayError {
+    fn from(e: serde_json::Error) -> Self {
+        ReplayError::Json(e)
+    }
+}
+
+// Result of a battle from the local player's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+// The JSON blocks at the start of a replay. The first one is the arena info,
+// later ones only exist in some game versions and are skipped.
+#[derive(Debug, Clone)]
+pub struct ReplayHeader {
+    pub arena: ArenaInfo,
+}
+
+impl ReplayHeader {
+    pub fn read(path: &Path) -> Result<Self, ReplayError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::from_reader(&mut reader)
+    }
+
+    // Leaves the reader positioned at the start of the packet stream
+    pub fn from_reader(reader: &mut impl Read) -> Result<Self, ReplayError> {
+        let magic = read_u32(reader)?;
+        if magic != REPLAY_MAGIC {
+            return Err(ReplayError::BadMagic(magic));
+        }
+
+        let block_count = read_u32(reader)?;
+        if block_count == 0 {
+            return Err(ReplayError::NoBlocks);
+        }
+
+        let arena = serde_json::from_slice(&read_block(reader)?)?;
+        for _ in 1..block_count {
+            read_block(reader)?;
+        }
+
+        Ok(Self { arena })
+    }
+}
+
+// Our own numbers from a replay's battle results
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BattleResults {
+    pub outcome: Outcome,
+    pub damage: u64,
+    pub frags: u32,
+}
+
+// JSON carried by the battle results packet, players are keyed by avatar id
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultsPacket {
+    winner_team_id: i64,
+    players: HashMap<String, ResultsPlayer>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultsPlayer {
+    team_id: i64,
+    #[serde(default)]
+    damage: u64,
+    #[serde(default)]
+    frags: u32,
+}
+
+// Results packet payload: a length prefixed JSON string. Only the keyed layout is
+// read; results sent as commonList/playersPublicInfo lists are positional and the
+// positions are not known here, so those are reported instead of guessed at.
+fn parse_results(payload: &[u8], own_avatar_id: i64) -> Result<Option<BattleResults>, ReplayError> {
+    let (length, json) = payload.split_at_checked(4).ok_or(ReplayError::Truncated)?;
+    let length = u32::from_le_bytes([length[0], length[1], length[2], length[3]]) as usize;
+    let json = json.get(..length).ok_or(ReplayError::Truncated)?;
+    let value: serde_json::Value = serde_json::from_slice(json)?;
+    if value.get("commonList").is_some() || value.get("playersPublicInfo").is_some() {
+        return Err(ReplayError::ResultsLayout);
+    }
+    let packet: ResultsPacket = serde_json::from_value(value)?;
+
+    let Some(own) = packet.players.get(&own_avatar_id.to_string()) else {
+        return Ok(None);
+    };
+    let outcome = match packet.winner_team_id {
+        winner if winner < 0 => Outcome::Draw,
+        winner if winner == own.team_id => Outcome::Win,
+        _ => Outcome::Loss,
+    };
+    Ok(Some(BattleResults {
+        outcome,
+        damage: own.damage,
+        frags: own.frags,
+    }))
+}
+
+// A fully loaded replay with its decrypted and inflated packet stream
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    packet_data: Vec<u8>,
+}
+
+impl Replay {
+    pub fn open(path: &Path) -> Result<Self, ReplayError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        Self::from_reader(&mut reader)
+    }
+
+    pub fn from_reader(reader: &mut impl Read) -> Result<Self, ReplayError> {
+        let header = ReplayHeader::from_reader(reader)?;
+
+        // Sizes of the inflated and compressed stream, not needed for decoding
+        let _decompressed_size = read_u32(reader)?;
+        let _compressed_size = read_u32(reader)?;
+
+        let mut encrypted = Vec::new();
+        reader.read_to_end(&mut encrypted)?;
+        let compressed = decrypt(&encrypted)?;
+
+        let mut packet_data = Vec::new();
+        ZlibDecoder::new(compressed.as_slice())
+            .read_to_end(&mut packet_data)
+            .map_err(ReplayError::Decompress)?;
+
+        Ok(Self {
+            header,
+            packet_data,
+        })
+    }
+
+    pub fn packets(&self) -> Packets<'_> {
+        Packets {
+            data: &self.packet_data,
+        }
+    }
+
+    // Only replays recorded to the end of the battle carry the results packet
+    pub fn results(&self) -> Option<BattleResults> {
+        let own = self
+            .header
+            .arena
+            .vehicles
+            .iter()
+            .find(|vehicle| vehicle.relation() == Relation::Own)?;
+        let packet = self
+            .packets()
+            .filter_map(Result::ok)
+            .find(|packet| packet.kind == BATTLE_RESULTS_PACKET)?;
+        parse_results(packet.payload, own.id).unwrap_or_else(|e| {
+            warn!("Unreadable battle results: {}", e);
+            None
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Packet<'a> {
+    pub kind: u32,
+    pub time: f32,
+    pub payload: &'a [u8],
+}
+
+pub struct Packets<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for Packets<'a> {
+    type Item = Result<Packet<'a>, ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        if self.data.len() < PACKET_HEADER_SIZE {
+            self.data = &[];
+            return Some(Err(ReplayError::Truncated));
+        }
+
+        let field = |offset: usize| {
+            let bytes = &self.data[offset..offset + 4];
+            [bytes[0], bytes[1], bytes[2], bytes[3]]
+        };
+        let size = u32::from_le_bytes(field(0)) as usize;
+        let kind = u32::from_le_bytes(field(4));
+        let time = f32::from_le_bytes(field(8));
+
+        let rest = &self.data[PACKET_HEADER_SIZE..];
+        if rest.len() < size {
+            self.data = &[];
+            return Some(Err(ReplayError::Truncated));
+        }
+        let (payload, rest) = rest.split_at(size);
+        self.data = rest;
+
+        Some(Ok(Packet {
+            kind,
+            time,
+            payload,
+        }))
+    }
+}
+
+// Blowfish in ECB mode, with every plain block XORed into the next one
+fn decrypt(encrypted: &[u8]) -> Result<Vec<u8>, ReplayError> {
+    if !encrypted.len().is_multiple_of(8) {
+        return Err(ReplayError::Truncated);
+    }
+
+    let cipher: Blowfish = Blowfish::new_from_slice(&BLOWFISH_KEY).expect("valid blowfish key");
+    let mut previous = [0u8; 8];
+    let mut decrypted = Vec::with_capacity(encrypted.len());
+    for chunk in encrypted.chunks_exact(8) {
+        let mut block = GenericArray::clone_from_slice(chunk);
+        cipher.decrypt_block(&mut block);
+        for (byte, prev) in block.iter_mut().zip(previous.iter()) {
+            *byte ^= prev;
+        }
+        previous.copy_from_slice(&block);
+        decrypted.extend_from_slice(&block);
+    }
+    Ok(decrypted)
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, ReplayError> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_block(reader: &mut impl Read) -> Result<Vec<u8>, ReplayError> {
+    let size = read_u32(reader)?;
+    if size > MAX_BLOCK_SIZE {
+        return Err(ReplayError::BlockTooLarge(size));
+    }
+    let mut block = vec![0; size as usize];
+    reader.read_exact(&mut block)?;
+    Ok(block)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blowfish::cipher::BlockEncrypt;
+
+    // Synthetic replay written in the client's container format: the arena block of
+    // a 3 vs 3 battle, then a few packets ending with keyed battle results. It is not
+    // a capture, see results_are_read_from_a_real_replay for checking a real one.
+    const FIXTURE: &[u8] = include_bytes!("../tests/fixtures/synthetic_3v3.wowsreplay");
+
+    fn encrypt(plain: &[u8]) -> Vec<u8> {
+        let cipher: Blowfish = Blowfish::new_from_slice(&BLOWFISH_KEY).unwrap();
+        let mut previous = [0u8; 8];
+        let mut encrypted = Vec::new();
+        for chunk in plain.chunks_exact(8) {
+            let mut block = GenericArray::clone_from_slice(chunk);
+            for (byte, prev) in block.iter_mut().zip(previous.iter()) {
+                *byte ^= prev;
+            }
+            previous.copy_from_slice(chunk);
+            cipher.encrypt_block(&mut block);
+            encrypted.extend_from_slice(&block);
+        }
+        encrypted
+    }
+
+    #[test]
+    fn header_holds_the_arena_block() {
+        let header = ReplayHeader::from_reader(&mut &FIXTURE[..]).unwrap();
+        assert_eq!(header.arena.player_name, "Tester");
+        assert_eq!(header.arena.client_version_from_exe, "13,5,0,8316123");
+        assert_eq!(header.arena.map_display_name, "17_NA_fault_line");
+        assert_eq!(header.arena.vehicles.len(), 6);
+        let (allies, enemies) = header.arena.split_teams();
+        assert_eq!((allies.len(), enemies.len()), (3, 3));
+    }
+
+    #[test]
+    fn decrypt_undoes_the_chained_encryption() {
+        let plain: Vec<u8> = (0..64).map(|i| (i * 7) as u8).collect();
+        let encrypted = encrypt(&plain);
+        assert_ne!(encrypted, plain);
+        assert_eq!(decrypt(&encrypted).unwrap(), plain);
+    }
+
+    #[test]
+    fn packets_are_decrypted_and_inflated() {
+        let replay = Replay::from_reader(&mut &FIXTURE[..]).unwrap();
+        let packets: Vec<Packet> = replay.packets().collect::<Result<_, _>>().unwrap();
+        let kinds: Vec<u32> = packets.iter().map(|packet| packet.kind).collect();
+        assert_eq!(kinds, [0x16, 0x00, 0x0A, 0x08, 0x22]);
+        assert_eq!(packets[0].time, 0.0);
+        assert_eq!(&packets[0].payload[4..], b"13,5,0,8316123");
+        assert_eq!(packets[2].time, 3.25);
+        assert_eq!(packets[4].time, 1187.75);
+    }
+
+    #[test]
+    fn results_come_from_our_entry_in_the_results_packet() {
+        let replay = Replay::from_reader(&mut &FIXTURE[..]).unwrap();
+        let results = replay.results().unwrap();
+        assert_eq!(results.outcome, Outcome::Win);
+        assert_eq!(results.damage, 84512);
+        assert_eq!(results.frags, 2);
+    }
+
+    #[test]
+    fn outcome_follows_our_team() {
+        let json = br#"{"winnerTeamId":1,"players":{"7":{"teamId":0,"damage":5,"frags":1},"8":{"teamId":1}}}"#;
+        let mut payload = (json.len() as u32).to_le_bytes().to_vec();
+        payload.extend_from_slice(json);
+
+        let ours = parse_results(&payload, 7).unwrap().unwrap();
+        assert_eq!(ours.outcome, Outcome::Loss);
+        assert_eq!((ours.damage, ours.frags), (5, 1));
+        assert_eq!(
+            parse_results(&payload, 8).unwrap().unwrap().outcome,
+            Outcome::Win
+        );
+        assert!(parse_results(&payload, 9).unwrap().is_none());
+        assert!(matches!(
+            parse_results(&payload[..10], 7),
+            Err(ReplayError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn positional_results_are_reported_not_guessed() {
+        let json = br#"{"arenaUniqueID":1,"commonList":[1,2,3],"playersPublicInfo":{"7":[0,1]}}"#;
+        let mut payload = (json.len() as u32).to_le_bytes().to_vec();
+        payload.extend_from_slice(json);
+        assert!(matches!(
+            parse_results(&payload, 7),
+            Err(ReplayError::ResultsLayout)
+        ));
+    }
+
+    // Needs a replay recorded to the end of a battle by the game client:
+    // WOWS_REPLAY=/path/to/file.wowsreplay cargo test -- --ignored
+    #[test]
+    #[ignore]
+    fn results_are_read_from_a_real_replay() {
+        let path = std::env::var_os("WOWS_REPLAY").expect("WOWS_REPLAY is not set");
+        let replay = Replay::open(Path::new(&path)).unwrap();
+        assert!(replay.results().is_some());
+    }
+
+    #[test]
+    fn bad_magic_is_rejected() {
+        let mut data = FIXTURE.to_vec();
+        data[0] ^= 0xFF;
+        assert!(matches!(
+            ReplayHeader::from_reader(&mut data.as_slice()),
+            Err(ReplayError::BadMagic(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_files_are_rejected() {
+        let header_cut = &FIXTURE[..40];
+        assert!(matches!(
+            ReplayHeader::from_reader(&mut &header_cut[..]),
+            Err(ReplayError::Truncated)
+        ));
+
+        let stream_cut = &FIXTURE[..FIXTURE.len() - 3];
+        assert!(matches!(
+            Replay::from_reader(&mut &stream_cut[..]),
+            Err(ReplayError::Truncated)
+        ));
+    }
+}
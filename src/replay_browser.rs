@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use iced::futures::channel::mpsc;
+use iced::futures::{future, SinkExt, StreamExt};
+use iced::subscription::{self, Subscription};
+use tracing::{info, warn};
+
+use crate::replay::{Replay, ReplayHeader, REPLAY_EXTENSION};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplaySortKey {
+    Date,
+    Map,
+    Mode,
+    Ship,
+    Duration,
+}
+
+impl ReplaySortKey {
+    pub const ALL: [ReplaySortKey; 5] = [
+        ReplaySortKey::Date,
+        ReplaySortKey::Map,
+        ReplaySortKey::Mode,
+        ReplaySortKey::Ship,
+        ReplaySortKey::Duration,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReplaySortKey::Date => "Date",
+            ReplaySortKey::Map => "Map",
+            ReplaySortKey::Mode => "Mode",
+            ReplaySortKey::Ship => "Ship",
+            ReplaySortKey::Duration => "Duration",
+        }
+    }
+}
+
+// One row of the replay browser
+#[derive(Debug, Clone)]
+pub struct ReplaySummary {
+    pub path: PathBuf,
+    pub date_time: String,
+    pub map: String,
+    pub mode: String,
+    pub ship: String,
+    // Seconds from the first to the last packet. Needs the whole replay decrypted,
+    // so it is filled in by `durations` after the header scan.
+    pub duration: Option<u32>,
+}
+
+impl ReplaySummary {
+    fn read(path: &Path) -> Option<Self> {
+        let header = match ReplayHeader::read(path) {
+            Ok(header) => header,
+            Err(e) => {
+                warn!("Skipping replay {:?}: {}", path, e);
+                return None;
+            }
+        };
+        let arena = header.arena;
+
+        Some(Self {
+            path: path.to_path_buf(),
+            date_time: arena.date_time,
+            map: arena.map_display_name,
+            mode: arena.match_group,
+            ship: arena.player_vehicle,
+            duration: None,
+        })
+    }
+
+    pub fn duration_label(&self) -> String {
+        match self.duration {
+            Some(duration) => format!("{}:{:02}", duration / 60, duration % 60),
+            None => "-".to_string(),
+        }
+    }
+
+    fn compare(&self, other: &Self, key: ReplaySortKey) -> Ordering {
+        match key {
+            ReplaySortKey::Date => {
+                date_sort_key(&self.date_time).cmp(&date_sort_key(&other.date_time))
+            }
+            ReplaySortKey::Map => self.map.cmp(&other.map),
+            ReplaySortKey::Mode => self.mode.cmp(&other.mode),
+            ReplaySortKey::Ship => self.ship.cmp(&other.ship),
+            ReplaySortKey::Duration => self.duration.cmp(&other.duration),
+        }
+    }
+}
+
+// The game writes "dd.mm.yyyy hh:mm:ss", reorder it so it sorts chronologically
+pub fn date_sort_key(date_time: &str) -> String {
+    match date_time.split_once(' ') {
+        Some((date, time)) => {
+            let mut parts: Vec<&str> = date.split('.').collect();
+            parts.reverse();
+            format!("{} {}", parts.join("."), time)
+        }
+        None => date_time.to_string(),
+    }
+}
+
+pub fn sort_replays(replays: &mut [ReplaySummary], key: ReplaySortKey, descending: bool) {
+    replays.sort_by(|a, b| {
+        let ordering = a.compare(b, key);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+// Time of the last packet, None when the replay can't be read
+pub fn replay_duration(path: &Path) -> Option<u32> {
+    let replay = match Replay::open(path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            warn!("Failed to read replay {:?}: {}", path, e);
+            return None;
+        }
+    };
+    replay
+        .packets()
+        .filter_map(Result::ok)
+        .last()
+        .map(|packet| packet.time as u32)
+}
+
+// Decrypt the replays one after another on their own thread and report every
+// duration as soon as it is known. Keyed by the list, so a rescan restarts it.
+pub fn durations(paths: Vec<PathBuf>) -> Subscription<(PathBuf, Option<u32>)> {
+    subscription::channel(paths.clone(), 16, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        thread::spawn(move || {
+            for path in paths {
+                let duration = replay_duration(&path);
+                if sender.unbounded_send((path, duration)).is_err() {
+                    return;
+                }
+            }
+        });
+        while let Some(loaded) = receiver.next().await {
+            let _ = output.send(loaded).await;
+        }
+        future::pending().await
+    })
+}
+
+// Read the header of every replay in the folder, newest first
+pub fn scan_replays(replay_path: &Path) -> Vec<ReplaySummary> {
+    info!("Scanning replays in: {:?}", replay_path);
+
+    let entries = match fs::read_dir(replay_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read replay folder: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut replays: Vec<ReplaySummary> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION))
+        .filter_map(|path| ReplaySummary::read(&path))
+        .collect();
+    sort_replays(&mut replays, ReplaySortKey::Date, true);
+
+    info!("Found {} replays", replays.len());
+    replays
+}
@@ -0,0 +1,266 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::arena::Relation;
+use crate::cache;
+use crate::config::Config;
+use crate::pr::ExpectedValues;
+use crate::replay::{BattleResults, Outcome, Replay, ReplayHeader, REPLAY_EXTENSION};
+use crate::replay_browser;
+use crate::stats::BattleStats;
+
+pub const SESSIONS_FILE: &str = "sessions.json";
+
+// One of our own battles, taken from its replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBattle {
+    // Arena date and time as written by the game
+    pub date_time: String,
+    // Replays only carry avatar ids, the account is looked up by name when needed
+    #[serde(default)]
+    pub player_name: String,
+    pub ship_id: u64,
+    // Game's internal name, used when the ship database does not know the ship
+    pub ship_name: String,
+    // None when the replay has no results packet
+    pub results: Option<BattleResults>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    // Unix timestamp in seconds
+    pub started_at: u64,
+    pub battles: Vec<SessionBattle>,
+    // Our lifetime PR, fetched after the first battle of the session, to compare the
+    // session's PR against
+    #[serde(default)]
+    pub baseline_pr: Option<f32>,
+}
+
+impl Session {
+    fn new() -> Self {
+        Self {
+            started_at: cache::now_secs(),
+            battles: Vec::new(),
+            baseline_pr: None,
+        }
+    }
+
+    pub fn own_player_name(&self) -> Option<&str> {
+        self.battles
+            .iter()
+            .map(|battle| battle.player_name.as_str())
+            .find(|name| !name.is_empty())
+    }
+
+    // Totals per ship, battles without results are left out
+    pub fn ship_totals(&self) -> Vec<(u64, String, BattleStats)> {
+        let mut ships: Vec<(u64, String, BattleStats)> = Vec::new();
+        for battle in &self.battles {
+            let Some(results) = battle.results else {
+                continue;
+            };
+            let index = match ships
+                .iter()
+                .position(|(ship_id, ..)| *ship_id == battle.ship_id)
+            {
+                Some(index) => index,
+                None => {
+                    ships.push((
+                        battle.ship_id,
+                        battle.ship_name.clone(),
+                        BattleStats::default(),
+                    ));
+                    ships.len() - 1
+                }
+            };
+            ships[index].2.merge(&BattleStats {
+                battles: 1,
+                wins: (results.outcome == Outcome::Win) as u32,
+                damage_dealt: results.damage,
+                frags: results.frags,
+            });
+        }
+        ships
+    }
+
+    pub fn totals(&self) -> BattleStats {
+        let mut total = BattleStats::default();
+        for (_, _, stats) in self.ship_totals() {
+            total.merge(&stats);
+        }
+        total
+    }
+
+    pub fn losses(&self) -> usize {
+        self.battles
+            .iter()
+            .filter(|battle| {
+                battle
+                    .results
+                    .is_some_and(|results| results.outcome == Outcome::Loss)
+            })
+            .count()
+    }
+
+    pub fn pr(&self, expected_values: &ExpectedValues) -> Option<f32> {
+        let ships: Vec<(u64, BattleStats)> = self
+            .ship_totals()
+            .into_iter()
+            .map(|(ship_id, _, stats)| (ship_id, stats))
+            .collect();
+        expected_values.account_pr(&ships)
+    }
+}
+
+// Every session we tracked, the last one is the running session
+pub struct SessionStore {
+    path: PathBuf,
+    sessions: Vec<Session>,
+}
+
+impl SessionStore {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(SESSIONS_FILE)
+    }
+
+    // Starts a fresh session unless the last one never saw a battle
+    pub fn load(path: &Path) -> Self {
+        info!("Loading sessions from: {:?}", path);
+
+        let sessions = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("Error parsing sessions: {}", e);
+                Vec::new()
+            }),
+            Err(e) => {
+                warn!("No sessions found: {}", e);
+                Vec::new()
+            }
+        };
+
+        let mut store = Self {
+            path: path.to_path_buf(),
+            sessions,
+        };
+        store.reset();
+        store
+    }
+
+    pub fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&self.sessions) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(&self.path, contents) {
+                    error!("Error saving sessions: {}", e);
+                }
+            }
+            Err(e) => error!("Error serializing sessions: {}", e),
+        }
+    }
+
+    pub fn sessions(&self) -> &[Session] {
+        &self.sessions
+    }
+
+    pub fn current(&self) -> &Session {
+        self.sessions.last().expect("a session is started on load")
+    }
+
+    pub fn current_mut(&mut self) -> &mut Session {
+        self.sessions
+            .last_mut()
+            .expect("a session is started on load")
+    }
+
+    pub fn reset(&mut self) {
+        if self
+            .sessions
+            .last()
+            .is_none_or(|session| !session.battles.is_empty())
+        {
+            self.sessions.push(Session::new());
+        } else {
+            *self.current_mut() = Session::new();
+        }
+    }
+
+    pub fn add_battles(&mut self, battles: Vec<SessionBattle>) {
+        let session = self.current_mut();
+        let known: HashSet<String> = session
+            .battles
+            .iter()
+            .map(|battle| battle.date_time.clone())
+            .collect();
+        session.battles.extend(
+            battles
+                .into_iter()
+                .filter(|battle| !known.contains(&battle.date_time)),
+        );
+        self.save();
+    }
+}
+
+// Our battles from replays written since `since` that are not in `known` yet
+pub fn scan_new_battles(
+    replay_path: &Path,
+    since: u64,
+    known: &HashSet<String>,
+) -> Vec<SessionBattle> {
+    let entries = match fs::read_dir(replay_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to read replay folder: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut battles: Vec<SessionBattle> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == REPLAY_EXTENSION)
+        })
+        .filter(|entry| {
+            entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| secs(modified) >= since)
+        })
+        .filter(|entry| {
+            ReplayHeader::read(&entry.path())
+                .is_ok_and(|header| !known.contains(&header.arena.date_time))
+        })
+        .filter_map(|entry| Replay::open(&entry.path()).ok())
+        .filter_map(|replay| {
+            let arena = &replay.header.arena;
+            let own = arena
+                .vehicles
+                .iter()
+                .find(|vehicle| vehicle.relation() == Relation::Own)?;
+            Some(SessionBattle {
+                date_time: arena.date_time.clone(),
+                player_name: arena.player_name.clone(),
+                ship_id: own.ship_id,
+                ship_name: arena.player_vehicle.clone(),
+                results: replay.results(),
+            })
+        })
+        .collect();
+    battles.sort_by_key(|battle| replay_browser::date_sort_key(&battle.date_time));
+    battles
+}
+
+fn secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+
+// Ship encyclopedia in the format of the public API `encyclopedia/ships` endpoint
+pub const SHIPS_FILE: &str = "ships.json";
+
+// Ships are added between game updates too, so refresh at least weekly
+const SHIPS_MAX_AGE_SECS: u64 = 7 * 24 * 3600;
+
+// Named after the API `type` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShipClass {
+    AirCarrier,
+    Battleship,
+    Cruiser,
+    Destroyer,
+    Submarine,
+}
+
+impl ShipClass {
+    // In lobby order
+    pub const ALL: [ShipClass; 5] = [
+        ShipClass::AirCarrier,
+        ShipClass::Battleship,
+        ShipClass::Cruiser,
+        ShipClass::Destroyer,
+        ShipClass::Submarine,
+    ];
+
+    pub fn short(self) -> &'static str {
+        match self {
+            ShipClass::AirCarrier => "CV",
+            ShipClass::Battleship => "BB",
+            ShipClass::Cruiser => "CA",
+            ShipClass::Destroyer => "DD",
+            ShipClass::Submarine => "SS",
+        }
+    }
+
+    // Position in the in-game lobby, carriers first
+    pub fn lobby_order(self) -> u8 {
+        match self {
+            ShipClass::AirCarrier => 0,
+            ShipClass::Battleship => 1,
+            ShipClass::Cruiser => 2,
+            ShipClass::Destroyer => 3,
+            ShipClass::Submarine => 4,
+        }
+    }
+
+    pub fn icon(self) -> &'static [u8] {
+        match self {
+            ShipClass::AirCarrier => include_bytes!("../assets/class_carrier.svg"),
+            ShipClass::Battleship => include_bytes!("../assets/class_battleship.svg"),
+            ShipClass::Cruiser => include_bytes!("../assets/class_cruiser.svg"),
+            ShipClass::Destroyer => include_bytes!("../assets/class_destroyer.svg"),
+            ShipClass::Submarine => include_bytes!("../assets/class_submarine.svg"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShipInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub class: ShipClass,
+    pub tier: u8,
+    pub nation: String,
+    #[serde(rename = "is_premium", default)]
+    pub premium: bool,
+    // Special ships are the non-premium reward and event ships
+    #[serde(rename = "is_special", default)]
+    pub special: bool,
+}
+
+impl ShipInfo {
+    pub fn tier_label(&self) -> &'static str {
+        tier_label(self.tier)
+    }
+}
+
+// Tier 11 are superships, shown with a star in game. The bundled font has no
+// star glyph, so they get a plain numeral like the other tiers.
+pub fn tier_label(tier: u8) -> &'static str {
+    const ROMAN: [&str; 11] = [
+        "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI",
+    ];
+    ROMAN
+        .get((tier as usize).wrapping_sub(1))
+        .copied()
+        .unwrap_or("?")
+}
+
+#[derive(Serialize, Deserialize)]
+struct ShipsFile<T> {
+    #[serde(default)]
+    game_version: String,
+    #[serde(default)]
+    downloaded_at: u64,
+    data: HashMap<String, T>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ShipDatabase {
+    ships: HashMap<u64, ShipInfo>,
+    // Client version the ships were downloaded for, empty when unknown
+    pub game_version: String,
+    // Unix seconds, 0 for databases saved before this was tracked
+    pub downloaded_at: u64,
+}
+
+impl ShipDatabase {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(SHIPS_FILE)
+    }
+
+    // Missing or broken files give an empty database, ships then show their id
+    pub fn load(path: &Path) -> Self {
+        info!("Loading ship database from: {:?}", path);
+
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                warn!("No ship database available: {}", e);
+                return Self::default();
+            }
+        };
+
+        match Self::parse(&contents) {
+            Ok(ships) => {
+                info!("Loaded {} ships", ships.len());
+                ships
+            }
+            Err(e) => {
+                error!("Error parsing ship database: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, serde_json::Error> {
+        // Entries the API lists without full data (e.g. test ships) are skipped
+        let file: ShipsFile<serde_json::Value> = serde_json::from_str(contents)?;
+        let ships = file
+            .data
+            .into_iter()
+            .filter_map(|(ship_id, entry)| {
+                Some((ship_id.parse().ok()?, serde_json::from_value(entry).ok()?))
+            })
+            .collect();
+        Ok(Self {
+            ships,
+            game_version: file.game_version,
+            downloaded_at: file.downloaded_at,
+        })
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let file = ShipsFile {
+            game_version: self.game_version.clone(),
+            downloaded_at: self.downloaded_at,
+            data: self
+                .ships
+                .iter()
+                .map(|(ship_id, info)| (ship_id.to_string(), info))
+                .collect(),
+        };
+        match serde_json::to_string(&file) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(path, contents) {
+                    error!("Error saving ship database: {}", e);
+                }
+            }
+            Err(e) => error!("Error serializing ship database: {}", e),
+        }
+    }
+
+    pub fn get(&self, ship_id: u64) -> Option<&ShipInfo> {
+        self.ships.get(&ship_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ships.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ships.is_empty()
+    }
+
+    // An empty game version means the running client is unknown, then only the age counts
+    pub fn is_outdated(&self, game_version: &str, now: u64) -> bool {
+        self.is_empty()
+            || (!game_version.is_empty() && game_version != self.game_version)
+            || now.saturating_sub(self.downloaded_at) > SHIPS_MAX_AGE_SECS
+    }
+}
+
+impl FromIterator<(u64, ShipInfo)> for ShipDatabase {
+    fn from_iter<I: IntoIterator<Item = (u64, ShipInfo)>>(iter: I) -> Self {
+        Self {
+            ships: iter.into_iter().collect(),
+            game_version: String::new(),
+            downloaded_at: 0,
+        }
+    }
+}
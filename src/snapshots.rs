@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::region::Region;
+use crate::stats::{BattleStats, PlayerStats};
+
+pub const SNAPSHOTS_FILE: &str = "stats_snapshots.json";
+
+const DAY_SECS: u64 = 24 * 3600;
+
+// Longest window we offer, older snapshots are dropped
+const KEEP_DAYS: u64 = 30;
+
+// Which numbers the player cards show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum StatsWindow {
+    #[default]
+    Lifetime,
+    LastWeek,
+    LastMonth,
+}
+
+impl StatsWindow {
+    pub const ALL: [StatsWindow; 3] = [
+        StatsWindow::Lifetime,
+        StatsWindow::LastWeek,
+        StatsWindow::LastMonth,
+    ];
+
+    pub fn days(self) -> Option<u64> {
+        match self {
+            StatsWindow::Lifetime => None,
+            StatsWindow::LastWeek => Some(7),
+            StatsWindow::LastMonth => Some(KEEP_DAYS),
+        }
+    }
+}
+
+impl fmt::Display for StatsWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            StatsWindow::Lifetime => "Lifetime",
+            StatsWindow::LastWeek => "Last 7 days",
+            StatsWindow::LastMonth => "Last 30 days",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+// Battles played inside a window, the difference between two snapshots
+#[derive(Debug, Clone, Copy)]
+pub struct RecentStats {
+    pub account: BattleStats,
+    // None when the ship was not in the older snapshot
+    pub ship: Option<BattleStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    // Unix timestamp in seconds
+    taken_at: u64,
+    account: BattleStats,
+    #[serde(default)]
+    ships: HashMap<u64, BattleStats>,
+}
+
+// Lifetime stats as we saw them over time, at most one snapshot per player and day
+pub struct SnapshotStore {
+    path: PathBuf,
+    players: HashMap<String, Vec<Snapshot>>,
+    // Set by `record`, so a batch of lookups is written once
+    dirty: bool,
+}
+
+impl SnapshotStore {
+    pub fn path() -> PathBuf {
+        Config::data_dir().join(SNAPSHOTS_FILE)
+    }
+
+    pub fn load(path: &Path) -> Self {
+        info!("Loading stats snapshots from: {:?}", path);
+
+        let players = match fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                error!("Error parsing stats snapshots: {}", e);
+                HashMap::new()
+            }),
+            Err(e) => {
+                warn!("No stats snapshots found: {}", e);
+                HashMap::new()
+            }
+        };
+
+        Self {
+            path: path.to_path_buf(),
+            players,
+            dirty: false,
+        }
+    }
+
+    // Rewrites the whole file, so callers save once after a batch of lookups
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&self.players) {
+            Ok(contents) => {
+                if let Err(e) = fs::write(&self.path, contents) {
+                    error!("Error saving stats snapshots: {}", e);
+                }
+            }
+            Err(e) => error!("Error serializing stats snapshots: {}", e),
+        }
+    }
+
+    fn key(region: Region, account_id: i64) -> String {
+        format!("{}:{}", region.code(), account_id)
+    }
+
+    // Freshly fetched stats update today's snapshot or start a new one
+    pub fn record(&mut self, region: Region, ship_id: u64, stats: &PlayerStats, now: u64) {
+        self.dirty = true;
+        // Prune everyone, players we stop meeting would otherwise be kept forever
+        for snapshots in self.players.values_mut() {
+            snapshots
+                .retain(|snapshot| now.saturating_sub(snapshot.taken_at) <= KEEP_DAYS * DAY_SECS);
+        }
+        self.players.retain(|_, snapshots| !snapshots.is_empty());
+
+        let snapshots = self
+            .players
+            .entry(Self::key(region, stats.account_id))
+            .or_default();
+
+        let today = now / DAY_SECS;
+        match snapshots.last_mut() {
+            Some(snapshot) if snapshot.taken_at / DAY_SECS == today => {
+                snapshot.account = stats.account;
+                snapshot.ships.insert(ship_id, stats.ship);
+            }
+            _ => snapshots.push(Snapshot {
+                taken_at: now,
+                account: stats.account,
+                ships: HashMap::from([(ship_id, stats.ship)]),
+            }),
+        }
+    }
+
+    // Difference to the oldest snapshot inside the window. None for lifetime and
+    // for players we have no snapshot of from before today.
+    pub fn recent(
+        &self,
+        region: Region,
+        ship_id: u64,
+        stats: &PlayerStats,
+        window: StatsWindow,
+        now: u64,
+    ) -> Option<RecentStats> {
+        let days = window.days()?;
+        let today = now / DAY_SECS;
+        let baseline = self
+            .players
+            .get(&Self::key(region, stats.account_id))?
+            .iter()
+            .filter(|snapshot| snapshot.taken_at / DAY_SECS != today)
+            .find(|snapshot| now.saturating_sub(snapshot.taken_at) <= days * DAY_SECS)?;
+
+        Some(RecentStats {
+            account: stats.account.since(&baseline.account),
+            ship: baseline
+                .ships
+                .get(&ship_id)
+                .map(|earlier| stats.ship.since(earlier)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_718_000_000;
+
+    fn store() -> SnapshotStore {
+        SnapshotStore::load(Path::new("/nonexistent/stats_snapshots.json"))
+    }
+
+    fn stats(account_battles: u32, ship_battles: u32) -> PlayerStats {
+        PlayerStats {
+            account_id: 42,
+            account: BattleStats {
+                battles: account_battles,
+                wins: account_battles / 2,
+                damage_dealt: account_battles as u64 * 40_000,
+                frags: account_battles,
+            },
+            ship: BattleStats {
+                battles: ship_battles,
+                wins: ship_battles / 2,
+                damage_dealt: ship_battles as u64 * 50_000,
+                frags: ship_battles,
+            },
+            clan: None,
+        }
+    }
+
+    #[test]
+    fn snapshots_from_today_are_no_baseline() {
+        let mut snapshots = store();
+        snapshots.record(Region::Eu, 1, &stats(1000, 100), NOW - 60);
+        let recent = snapshots.recent(Region::Eu, 1, &stats(1010, 105), StatsWindow::LastWeek, NOW);
+        assert!(recent.is_none());
+        assert!(snapshots
+            .recent(Region::Eu, 1, &stats(1010, 105), StatsWindow::Lifetime, NOW)
+            .is_none());
+    }
+
+    #[test]
+    fn windows_start_at_the_oldest_snapshot_inside_them() {
+        let mut snapshots = store();
+        snapshots.record(Region::Eu, 1, &stats(900, 80), NOW - 20 * DAY_SECS);
+        snapshots.record(Region::Eu, 1, &stats(1000, 100), NOW - 5 * DAY_SECS);
+        let current = stats(1010, 105);
+
+        let week = snapshots
+            .recent(Region::Eu, 1, &current, StatsWindow::LastWeek, NOW)
+            .unwrap();
+        assert_eq!(week.account.battles, 10);
+        assert_eq!(week.ship.unwrap().battles, 5);
+
+        let month = snapshots
+            .recent(Region::Eu, 1, &current, StatsWindow::LastMonth, NOW)
+            .unwrap();
+        assert_eq!(month.account.battles, 110);
+        assert_eq!(month.ship.unwrap().battles, 25);
+
+        // Other regions are other accounts
+        assert!(snapshots
+            .recent(Region::Na, 1, &current, StatsWindow::LastWeek, NOW)
+            .is_none());
+    }
+
+    #[test]
+    fn snapshots_past_the_longest_window_are_dropped() {
+        let mut snapshots = store();
+        snapshots.record(Region::Eu, 1, &stats(900, 80), NOW - 40 * DAY_SECS);
+        snapshots.record(Region::Eu, 1, &stats(1000, 100), NOW);
+        let recent = snapshots.recent(
+            Region::Eu,
+            1,
+            &stats(1010, 105),
+            StatsWindow::LastMonth,
+            NOW,
+        );
+        assert!(recent.is_none());
+    }
+
+    #[test]
+    fn ships_missing_from_the_baseline_have_no_recent_numbers() {
+        let mut snapshots = store();
+        snapshots.record(Region::Eu, 1, &stats(1000, 100), NOW - 3 * DAY_SECS);
+        let recent = snapshots
+            .recent(Region::Eu, 2, &stats(1010, 5), StatsWindow::LastWeek, NOW)
+            .unwrap();
+        assert_eq!(recent.account.battles, 10);
+        assert!(recent.ship.is_none());
+    }
+
+    #[test]
+    fn no_battles_since_the_baseline_is_still_reported() {
+        let mut snapshots = store();
+        snapshots.record(Region::Eu, 1, &stats(1000, 100), NOW - 3 * DAY_SECS);
+        let recent = snapshots
+            .recent(Region::Eu, 1, &stats(1000, 100), StatsWindow::LastWeek, NOW)
+            .unwrap();
+        assert_eq!(recent.account.battles, 0);
+    }
+
+    #[test]
+    fn recording_anyone_drops_everyone_past_the_longest_window() {
+        let mut snapshots = store();
+        let mut other = stats(500, 50);
+        other.account_id = 7;
+        snapshots.record(Region::Eu, 1, &other, NOW - 40 * DAY_SECS);
+        snapshots.record(Region::Eu, 1, &stats(1000, 100), NOW);
+        assert!(!snapshots
+            .players
+            .contains_key(&SnapshotStore::key(Region::Eu, 7)));
+        assert_eq!(snapshots.players.len(), 1);
+    }
+}
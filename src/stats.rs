@@ -0,0 +1,683 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::region::Region;
+use crate::ships::{ShipDatabase, ShipInfo};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub enum StatsError {
+    Http(String),
+    Api { code: u32, message: String },
+    Parse(String),
+    NotFound,
+    HiddenProfile,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::Http(e) => write!(f, "request failed: {}", e),
+            StatsError::Api { code, message } => write!(f, "API error {}: {}", code, message),
+            StatsError::Parse(e) => write!(f, "unexpected response: {}", e),
+            StatsError::NotFound => write!(f, "account not found"),
+            StatsError::HiddenProfile => write!(f, "profile is hidden"),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+// Random battle totals, either for a whole account or a single ship
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct BattleStats {
+    #[serde(default)]
+    pub battles: u32,
+    #[serde(default)]
+    pub wins: u32,
+    #[serde(default)]
+    pub damage_dealt: u64,
+    #[serde(default)]
+    pub frags: u32,
+}
+
+impl BattleStats {
+    pub fn winrate(&self) -> f32 {
+        self.per_battle(self.wins as f32) * 100.0
+    }
+
+    pub fn avg_damage(&self) -> f32 {
+        self.per_battle(self.damage_dealt as f32)
+    }
+
+    pub fn avg_frags(&self) -> f32 {
+        self.per_battle(self.frags as f32)
+    }
+
+    // Battles played since an older snapshot of the same totals
+    pub fn since(&self, earlier: &BattleStats) -> BattleStats {
+        BattleStats {
+            battles: self.battles.saturating_sub(earlier.battles),
+            wins: self.wins.saturating_sub(earlier.wins),
+            damage_dealt: self.damage_dealt.saturating_sub(earlier.damage_dealt),
+            frags: self.frags.saturating_sub(earlier.frags),
+        }
+    }
+
+    pub fn merge(&mut self, other: &BattleStats) {
+        self.battles += other.battles;
+        self.wins += other.wins;
+        self.damage_dealt += other.damage_dealt;
+        self.frags += other.frags;
+    }
+
+    fn per_battle(&self, total: f32) -> f32 {
+        if self.battles == 0 {
+            0.0
+        } else {
+            total / self.battles as f32
+        }
+    }
+}
+
+// Clan an account belongs to. The public API has no clan colour, nor the clan
+// battle league one could be derived from, so only the id and tag are kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClanInfo {
+    pub clan_id: i64,
+    pub tag: String,
+}
+
+pub trait StatsProvider: Send + Sync {
+    fn find_account_id(&self, region: Region, name: &str) -> Result<i64, StatsError>;
+
+    fn account_stats(&self, region: Region, account_id: i64) -> Result<BattleStats, StatsError>;
+
+    fn ship_stats(
+        &self,
+        region: Region,
+        account_id: i64,
+        ship_id: u64,
+    ) -> Result<BattleStats, StatsError>;
+
+    // Every ship the account has taken into a random battle
+    fn all_ship_stats(
+        &self,
+        region: Region,
+        account_id: i64,
+    ) -> Result<Vec<(u64, BattleStats)>, StatsError>;
+
+    // None when the account is not in a clan
+    fn clan(&self, region: Region, account_id: i64) -> Result<Option<ClanInfo>, StatsError>;
+
+    // One page of the ship encyclopedia and the total page count
+    fn ship_page(
+        &self,
+        region: Region,
+        page_no: u32,
+    ) -> Result<(Vec<(u64, ShipInfo)>, u32), StatsError>;
+}
+
+// Spaces out calls to the wrapped provider to stay within the API request budget
+pub struct RateLimited<P> {
+    inner: P,
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl<P> RateLimited<P> {
+    pub fn new(inner: P, requests_per_second: u32) -> Self {
+        Self {
+            inner,
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    // Reserve the next free slot and sleep until it comes up. Blocks the calling
+    // thread, so callers run on a worker thread (see `worker::run`).
+    fn wait(&self) {
+        let slot = {
+            let mut next_slot = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let slot = (*next_slot).max(Instant::now());
+            *next_slot = slot + self.interval;
+            slot
+        };
+        let now = Instant::now();
+        if slot > now {
+            thread::sleep(slot - now);
+        }
+    }
+}
+
+impl<P: StatsProvider> StatsProvider for RateLimited<P> {
+    fn find_account_id(&self, region: Region, name: &str) -> Result<i64, StatsError> {
+        self.wait();
+        self.inner.find_account_id(region, name)
+    }
+
+    fn account_stats(&self, region: Region, account_id: i64) -> Result<BattleStats, StatsError> {
+        self.wait();
+        self.inner.account_stats(region, account_id)
+    }
+
+    fn ship_stats(
+        &self,
+        region: Region,
+        account_id: i64,
+        ship_id: u64,
+    ) -> Result<BattleStats, StatsError> {
+        self.wait();
+        self.inner.ship_stats(region, account_id, ship_id)
+    }
+
+    fn all_ship_stats(
+        &self,
+        region: Region,
+        account_id: i64,
+    ) -> Result<Vec<(u64, BattleStats)>, StatsError> {
+        self.wait();
+        self.inner.all_ship_stats(region, account_id)
+    }
+
+    fn clan(&self, region: Region, account_id: i64) -> Result<Option<ClanInfo>, StatsError> {
+        self.wait();
+        self.inner.clan(region, account_id)
+    }
+
+    fn ship_page(
+        &self,
+        region: Region,
+        page_no: u32,
+    ) -> Result<(Vec<(u64, ShipInfo)>, u32), StatsError> {
+        self.wait();
+        self.inner.ship_page(region, page_no)
+    }
+}
+
+// Everything the player card needs for one player
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub account_id: i64,
+    pub account: BattleStats,
+    pub ship: BattleStats,
+    #[serde(default)]
+    pub clan: Option<ClanInfo>,
+}
+
+// Neither the arena file nor replays carry account ids, so every lookup starts
+// by resolving the name on the selected region
+pub fn fetch_player_stats(
+    provider: &dyn StatsProvider,
+    region: Region,
+    name: &str,
+    ship_id: u64,
+) -> Result<PlayerStats, StatsError> {
+    let account_id = provider.find_account_id(region, name)?;
+    // Only the tag depends on it, so a failing clan lookup keeps the rest of the card
+    let clan = provider.clan(region, account_id).unwrap_or_else(|e| {
+        warn!("Clan lookup for {} failed: {}", name, e);
+        None
+    });
+
+    Ok(PlayerStats {
+        account_id,
+        account: provider.account_stats(region, account_id)?,
+        ship: provider.ship_stats(region, account_id, ship_id)?,
+        clan,
+    })
+}
+
+// The whole ship encyclopedia, a few hundred ships spread over several pages
+pub fn fetch_ships(
+    provider: &dyn StatsProvider,
+    region: Region,
+) -> Result<ShipDatabase, StatsError> {
+    let mut ships = Vec::new();
+    let mut page_no = 1;
+    loop {
+        let (page, page_total) = provider.ship_page(region, page_no)?;
+        ships.extend(page);
+        if page_no >= page_total {
+            break;
+        }
+        page_no += 1;
+    }
+    Ok(ships.into_iter().collect())
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    status: String,
+    data: Option<T>,
+    error: Option<ApiErrorBody>,
+    meta: Option<ApiMeta>,
+}
+
+#[derive(Deserialize)]
+struct ApiMeta {
+    // Only set by paged endpoints
+    page_total: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct ApiErrorBody {
+    code: u32,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct AccountListEntry {
+    account_id: i64,
+}
+
+#[derive(Deserialize)]
+struct AccountInfo {
+    #[serde(default)]
+    hidden_profile: bool,
+    statistics: Option<AccountStatistics>,
+}
+
+#[derive(Deserialize)]
+struct AccountStatistics {
+    pvp: Option<BattleStats>,
+}
+
+#[derive(Deserialize)]
+struct ClanMembership {
+    clan_id: Option<i64>,
+    clan: Option<ClanDetails>,
+}
+
+#[derive(Deserialize)]
+struct ClanDetails {
+    tag: String,
+}
+
+#[derive(Deserialize)]
+struct ShipEntry {
+    ship_id: u64,
+    pvp: Option<BattleStats>,
+}
+
+// Stats from the Wargaming public API (developers.wargaming.net)
+pub struct WargamingApi {
+    application_id: String,
+    // Overrides the per-region host, e.g. to point at a local mock server
+    base_url: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl WargamingApi {
+    pub fn new(application_id: String, base_url: Option<String>) -> Self {
+        Self {
+            application_id,
+            base_url,
+            agent: ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build(),
+        }
+    }
+
+    fn get<T: DeserializeOwned>(
+        &self,
+        region: Region,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T, StatsError> {
+        self.get_page(region, endpoint, query).map(|(data, _)| data)
+    }
+
+    // Data plus the total page count for paged endpoints
+    fn get_page<T: DeserializeOwned>(
+        &self,
+        region: Region,
+        endpoint: &str,
+        query: &[(&str, &str)],
+    ) -> Result<(T, u32), StatsError> {
+        let base_url = self.base_url.as_deref().unwrap_or(region.api_host());
+        let url = format!("{}/wows/{}/", base_url.trim_end_matches('/'), endpoint);
+        info!("Requesting {} {:?}", url, query);
+
+        let mut request = self
+            .agent
+            .get(&url)
+            .query("application_id", &self.application_id);
+        for (key, value) in query {
+            request = request.query(key, value);
+        }
+
+        let response: ApiResponse<T> = request
+            .call()
+            .map_err(|e| StatsError::Http(e.to_string()))?
+            .into_json()
+            .map_err(|e| StatsError::Parse(e.to_string()))?;
+
+        match (response.status.as_str(), response.data, response.error) {
+            ("ok", Some(data), _) => Ok((
+                data,
+                response.meta.and_then(|meta| meta.page_total).unwrap_or(1),
+            )),
+            (_, _, Some(error)) => {
+                warn!("API error from {}: {}", url, error.message);
+                Err(StatsError::Api {
+                    code: error.code,
+                    message: error.message,
+                })
+            }
+            _ => Err(StatsError::Parse(format!(
+                "status {} without data",
+                response.status
+            ))),
+        }
+    }
+}
+
+impl StatsProvider for WargamingApi {
+    fn find_account_id(&self, region: Region, name: &str) -> Result<i64, StatsError> {
+        let accounts: Vec<AccountListEntry> = self.get(
+            region,
+            "account/list",
+            &[("search", name), ("type", "exact")],
+        )?;
+        accounts
+            .first()
+            .map(|account| account.account_id)
+            .ok_or(StatsError::NotFound)
+    }
+
+    fn account_stats(&self, region: Region, account_id: i64) -> Result<BattleStats, StatsError> {
+        let id = account_id.to_string();
+        let mut accounts: HashMap<String, Option<AccountInfo>> =
+            self.get(region, "account/info", &[("account_id", &id)])?;
+
+        let info = accounts.remove(&id).flatten().ok_or(StatsError::NotFound)?;
+        if info.hidden_profile {
+            return Err(StatsError::HiddenProfile);
+        }
+        Ok(info
+            .statistics
+            .and_then(|statistics| statistics.pvp)
+            .unwrap_or_default())
+    }
+
+    fn ship_stats(
+        &self,
+        region: Region,
+        account_id: i64,
+        ship_id: u64,
+    ) -> Result<BattleStats, StatsError> {
+        let id = account_id.to_string();
+        let ship = ship_id.to_string();
+        let mut accounts: HashMap<String, Option<Vec<ShipEntry>>> = self.get(
+            region,
+            "ships/stats",
+            &[("account_id", &id), ("ship_id", &ship)],
+        )?;
+
+        // Hidden profiles are already caught by `account_stats`. Filtered by ship,
+        // null or an empty list both mean no battles in it.
+        let ships = accounts.remove(&id).flatten().unwrap_or_default();
+        Ok(ships
+            .into_iter()
+            .find(|entry| entry.ship_id == ship_id)
+            .and_then(|entry| entry.pvp)
+            .unwrap_or_default())
+    }
+
+    fn all_ship_stats(
+        &self,
+        region: Region,
+        account_id: i64,
+    ) -> Result<Vec<(u64, BattleStats)>, StatsError> {
+        let id = account_id.to_string();
+        let mut accounts: HashMap<String, Option<Vec<ShipEntry>>> =
+            self.get(region, "ships/stats", &[("account_id", &id)])?;
+
+        let ships = accounts
+            .remove(&id)
+            .flatten()
+            .ok_or(StatsError::HiddenProfile)?;
+        Ok(ships
+            .into_iter()
+            .filter_map(|entry| Some((entry.ship_id, entry.pvp?)))
+            .filter(|(_, stats)| stats.battles > 0)
+            .collect())
+    }
+
+    fn clan(&self, region: Region, account_id: i64) -> Result<Option<ClanInfo>, StatsError> {
+        let id = account_id.to_string();
+        let mut accounts: HashMap<String, Option<ClanMembership>> = self.get(
+            region,
+            "clans/accountinfo",
+            &[("account_id", &id), ("extra", "clan")],
+        )?;
+
+        let membership = accounts.remove(&id).flatten();
+        Ok(membership.and_then(|membership| {
+            Some(ClanInfo {
+                clan_id: membership.clan_id?,
+                tag: membership.clan?.tag,
+            })
+        }))
+    }
+
+    fn ship_page(
+        &self,
+        region: Region,
+        page_no: u32,
+    ) -> Result<(Vec<(u64, ShipInfo)>, u32), StatsError> {
+        let page_no = page_no.to_string();
+        let (data, page_total): (HashMap<String, serde_json::Value>, u32) = self.get_page(
+            region,
+            "encyclopedia/ships",
+            &[
+                ("fields", "name,type,tier,nation,is_premium,is_special"),
+                ("page_no", &page_no),
+            ],
+        )?;
+        // Like `ShipDatabase::parse`, entries without full data are skipped instead
+        // of failing the whole page
+        let ships = data
+            .into_iter()
+            .filter_map(|(ship_id, entry)| {
+                Some((ship_id.parse().ok()?, serde_json::from_value(entry).ok()?))
+            })
+            .collect();
+        Ok((ships, page_total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn since_is_the_difference_and_never_negative() {
+        let now = BattleStats {
+            battles: 110,
+            wins: 60,
+            damage_dealt: 5_500_000,
+            frags: 120,
+        };
+        let earlier = BattleStats {
+            battles: 100,
+            wins: 52,
+            damage_dealt: 5_000_000,
+            frags: 110,
+        };
+        let since = now.since(&earlier);
+        assert_eq!(
+            (since.battles, since.wins, since.damage_dealt, since.frags),
+            (10, 8, 500_000, 10)
+        );
+
+        // A reset or a snapshot from another account must not underflow
+        let since = earlier.since(&now);
+        assert_eq!(
+            (since.battles, since.wins, since.damage_dealt, since.frags),
+            (0, 0, 0, 0)
+        );
+    }
+
+    // Tiny stand-in for the Wargaming API, answers each request with the body `respond(path, query)`
+    fn mock_api(respond: fn(&str, &str) -> String) -> WargamingApi {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+                let (path, query) = target.split_once('?').unwrap_or((target, ""));
+                let body = respond(path, query);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+        WargamingApi::new("test".to_string(), Some(base_url))
+    }
+
+    fn param<'a>(query: &'a str, key: &str) -> &'a str {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+            .unwrap_or_default()
+    }
+
+    fn ok(data: &str) -> String {
+        format!(r#"{{"status":"ok","data":{}}}"#, data)
+    }
+
+    // "Alpha" (42) plays ship 1 but never took ships 2 and 3 out (the API answers
+    // with an empty list or null for those), "Ghost" (43) hides their
+    // profile and the clan endpoint fails for "Loner" (44)
+    fn respond(path: &str, query: &str) -> String {
+        let account_id = param(query, "account_id");
+        if path == "/wows/clans/accountinfo/" && account_id == "44" {
+            return r#"{"status":"error","error":{"code":504,"message":"SOURCE_NOT_AVAILABLE"}}"#
+                .to_string();
+        }
+        let data = match (path, param(query, "search"), account_id) {
+            ("/wows/account/list/", "Alpha", _) => r#"[{"nickname":"Alpha","account_id":42}]"#,
+            ("/wows/account/list/", "Ghost", _) => r#"[{"nickname":"Ghost","account_id":43}]"#,
+            ("/wows/account/list/", "Loner", _) => r#"[{"nickname":"Loner","account_id":44}]"#,
+            ("/wows/account/list/", _, _) => "[]",
+            ("/wows/account/info/", _, "42") => {
+                r#"{"42":{"hidden_profile":false,"statistics":{"pvp":{"battles":1000,"wins":550,"damage_dealt":40000000,"frags":700}}}}"#
+            }
+            ("/wows/account/info/", _, "43") => {
+                r#"{"43":{"hidden_profile":true,"statistics":null}}"#
+            }
+            ("/wows/account/info/", _, "44") => {
+                r#"{"44":{"hidden_profile":false,"statistics":{"pvp":{"battles":10,"wins":5}}}}"#
+            }
+            ("/wows/ships/stats/", _, "42") if param(query, "ship_id") == "1" => {
+                r#"{"42":[{"ship_id":1,"pvp":{"battles":50,"wins":30,"damage_dealt":3000000,"frags":40}}]}"#
+            }
+            ("/wows/ships/stats/", _, "42") if param(query, "ship_id") == "3" => r#"{"42":null}"#,
+            ("/wows/ships/stats/", _, "42") => r#"{"42":[]}"#,
+            ("/wows/ships/stats/", _, "44") => r#"{"44":[]}"#,
+            ("/wows/encyclopedia/ships/", _, _) => {
+                r#"{"1":{"name":"Alabama","type":"Battleship","tier":8,"nation":"usa","is_premium":true},
+                    "2":{"name":"Test ship","type":"Unknown","tier":8,"nation":"usa"},
+                    "3":{"name":"No tier","type":"Cruiser","nation":"usa"},
+                    "4":null}"#
+            }
+            ("/wows/clans/accountinfo/", _, "42") => r#"{"42":{"clan_id":7,"clan":{"tag":"PRO"}}}"#,
+            _ => "{}",
+        };
+        ok(data)
+    }
+
+    #[test]
+    fn resolves_name_then_loads_account_ship_and_clan() {
+        let api = mock_api(respond);
+        let stats = fetch_player_stats(&api, Region::Eu, "Alpha", 1).unwrap();
+
+        assert_eq!(stats.account_id, 42);
+        assert_eq!(stats.account.battles, 1000);
+        assert_eq!(stats.account.wins, 550);
+        assert_eq!(stats.ship.battles, 50);
+        assert_eq!(stats.ship.frags, 40);
+        let clan = stats.clan.unwrap();
+        assert_eq!((clan.clan_id, clan.tag.as_str()), (7, "PRO"));
+    }
+
+    #[test]
+    fn ship_never_played_gives_empty_ship_stats() {
+        let api = mock_api(respond);
+        let stats = fetch_player_stats(&api, Region::Eu, "Alpha", 2).unwrap();
+
+        assert_eq!(stats.account.battles, 1000);
+        assert_eq!(stats.ship.battles, 0);
+    }
+
+    #[test]
+    fn null_ship_entry_is_no_battles_not_a_hidden_profile() {
+        let api = mock_api(respond);
+        let stats = fetch_player_stats(&api, Region::Eu, "Alpha", 3).unwrap();
+
+        assert_eq!(stats.account.battles, 1000);
+        assert_eq!(stats.ship.battles, 0);
+    }
+
+    #[test]
+    fn hidden_profile_is_reported() {
+        let api = mock_api(respond);
+        let result = fetch_player_stats(&api, Region::Eu, "Ghost", 1);
+
+        assert!(matches!(result, Err(StatsError::HiddenProfile)));
+    }
+
+    #[test]
+    fn failing_clan_lookup_keeps_the_stats() {
+        let api = mock_api(respond);
+        let stats = fetch_player_stats(&api, Region::Eu, "Loner", 1).unwrap();
+
+        assert_eq!(stats.account.battles, 10);
+        assert!(stats.clan.is_none());
+    }
+
+    #[test]
+    fn incomplete_encyclopedia_entries_are_skipped() {
+        let api = mock_api(respond);
+        let ships = fetch_ships(&api, Region::Eu).unwrap();
+
+        assert_eq!(ships.len(), 1);
+        assert_eq!(ships.get(1).unwrap().name, "Alabama");
+    }
+
+    #[test]
+    fn unknown_name_is_not_found() {
+        let api = mock_api(respond);
+        let result = fetch_player_stats(&api, Region::Eu, "Nobody", 1);
+
+        assert!(matches!(result, Err(StatsError::NotFound)));
+    }
+}
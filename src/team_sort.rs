@@ -0,0 +1,95 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Player;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Team {
+    Team1,
+    Team2,
+}
+
+// Order of the players inside a team column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TeamSortKey {
+    // Like the in-game lobby
+    #[default]
+    ShipClass,
+    Pr,
+    AccountWinrate,
+    Battles,
+    ShipWinrate,
+    AvgDamage,
+    Frags,
+}
+
+impl TeamSortKey {
+    pub const ALL: [TeamSortKey; 7] = [
+        TeamSortKey::ShipClass,
+        TeamSortKey::Pr,
+        TeamSortKey::AccountWinrate,
+        TeamSortKey::Battles,
+        TeamSortKey::ShipWinrate,
+        TeamSortKey::AvgDamage,
+        TeamSortKey::Frags,
+    ];
+
+    // Value used for the stat sorts, None for players without it
+    fn value(self, player: &Player) -> Option<f32> {
+        match self {
+            TeamSortKey::ShipClass => None,
+            TeamSortKey::Pr => player.ship?.pr.map(|pr| pr as f32),
+            TeamSortKey::AccountWinrate => player.account.map(|account| account.winrate),
+            TeamSortKey::Battles => player.account.map(|account| account.battles as f32),
+            TeamSortKey::ShipWinrate => player.ship.map(|ship| ship.winrate),
+            TeamSortKey::AvgDamage => player.ship.map(|ship| ship.avg_damage),
+            TeamSortKey::Frags => player.ship.map(|ship| ship.frags),
+        }
+    }
+
+    fn compare(self, a: &Player, b: &Player) -> Ordering {
+        match self {
+            // Class in lobby order, then highest tier first, unknown ships last
+            TeamSortKey::ShipClass => match (&a.ship_info, &b.ship_info) {
+                (Some(a), Some(b)) => (a.class.lobby_order(), b.tier, &a.name).cmp(&(
+                    b.class.lobby_order(),
+                    a.tier,
+                    &b.name,
+                )),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            // Best first, players without the stat at the bottom
+            _ => match (self.value(a), self.value(b)) {
+                (Some(a), Some(b)) => b.total_cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        }
+    }
+}
+
+impl fmt::Display for TeamSortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            TeamSortKey::ShipClass => "Class / Tier",
+            TeamSortKey::Pr => "PR",
+            TeamSortKey::AccountWinrate => "Account WR",
+            TeamSortKey::Battles => "Battles",
+            TeamSortKey::ShipWinrate => "Ship WR",
+            TeamSortKey::AvgDamage => "Avg Damage",
+            TeamSortKey::Frags => "Avg Frags",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+pub fn sorted_players(players: &[Player], key: TeamSortKey) -> Vec<&Player> {
+    let mut sorted: Vec<&Player> = players.iter().collect();
+    sorted.sort_by(|a, b| key.compare(a, b));
+    sorted
+}
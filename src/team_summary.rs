@@ -0,0 +1,175 @@
+use crate::rating::Rating;
+use crate::ships::ShipClass;
+use crate::Player;
+
+// Aggregate numbers for one side, compared against the other above the columns
+#[derive(Debug, Clone, Default)]
+pub struct TeamSummary {
+    pub avg_pr: Option<f32>,
+    // Weighted by battles in the ship, so a handful of lucky games counts less
+    pub weighted_pr: Option<f32>,
+    pub avg_account_winrate: Option<f32>,
+    pub avg_ship_winrate: Option<f32>,
+    pub total_battles: u64,
+    // Players whose PR in their ship rates as Unicum or better
+    pub unicums: usize,
+    // Per class in lobby order, classes nobody brought are left out
+    pub classes: Vec<ClassSummary>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClassSummary {
+    pub class: ShipClass,
+    pub count: usize,
+    // Averages over the players of the class that have ship stats
+    pub avg_pr: Option<f32>,
+    pub avg_winrate: Option<f32>,
+}
+
+// Players without stats (loading, hidden, bots) are left out of the averages
+pub fn summarize(players: &[Player]) -> TeamSummary {
+    let prs: Vec<(f32, u32)> = players
+        .iter()
+        .filter_map(|player| {
+            let ship = player.ship?;
+            Some((ship.pr? as f32, ship.battles))
+        })
+        .collect();
+
+    let weight: f32 = prs.iter().map(|&(_, battles)| battles as f32).sum();
+    let weighted_pr = (weight > 0.0).then(|| {
+        prs.iter()
+            .map(|&(pr, battles)| pr * battles as f32)
+            .sum::<f32>()
+            / weight
+    });
+
+    let classes = ShipClass::ALL
+        .iter()
+        .map(|&class| {
+            let in_class: Vec<&Player> = players
+                .iter()
+                .filter(|player| player.ship_info.as_ref().map(|info| info.class) == Some(class))
+                .collect();
+            ClassSummary {
+                class,
+                count: in_class.len(),
+                avg_pr: average(
+                    in_class
+                        .iter()
+                        .filter_map(|player| Some(player.ship?.pr? as f32)),
+                ),
+                avg_winrate: average(
+                    in_class
+                        .iter()
+                        .filter_map(|player| Some(player.ship?.winrate)),
+                ),
+            }
+        })
+        .filter(|summary| summary.count > 0)
+        .collect();
+
+    TeamSummary {
+        avg_pr: average(prs.iter().map(|&(pr, _)| pr)),
+        weighted_pr,
+        avg_account_winrate: average(
+            players
+                .iter()
+                .filter_map(|player| Some(player.account?.winrate)),
+        ),
+        avg_ship_winrate: average(
+            players
+                .iter()
+                .filter_map(|player| Some(player.ship?.winrate)),
+        ),
+        total_battles: players
+            .iter()
+            .filter_map(|player| player.account)
+            .map(|account| account.battles as u64)
+            .sum(),
+        unicums: prs
+            .iter()
+            .filter(|&&(pr, _)| Rating::from_pr(pr) >= Rating::Unicum)
+            .count(),
+        classes,
+    }
+}
+
+fn average(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_and_battle_weighted_pr() {
+        let players = [
+            Player::sample(ShipClass::Battleship, Some((100, 50.0, 1000))),
+            Player::sample(ShipClass::Cruiser, Some((300, 60.0, 2000))),
+        ];
+        let summary = summarize(&players);
+        assert_eq!(summary.avg_pr, Some(1500.0));
+        assert_eq!(summary.weighted_pr, Some(1750.0));
+        assert_eq!(summary.avg_account_winrate, Some(55.0));
+        assert_eq!(summary.avg_ship_winrate, Some(55.0));
+        assert_eq!(summary.total_battles, 4000);
+    }
+
+    #[test]
+    fn unicums_are_counted_by_ship_pr() {
+        let players = [
+            Player::sample(ShipClass::Destroyer, Some((50, 65.0, 2600))),
+            Player::sample(ShipClass::Destroyer, Some((50, 60.0, 2200))),
+            Player::sample(ShipClass::Destroyer, Some((50, 50.0, 900))),
+        ];
+        assert_eq!(summarize(&players).unicums, 2);
+    }
+
+    #[test]
+    fn classes_are_aggregated_in_lobby_order() {
+        let players = [
+            Player::sample(ShipClass::Destroyer, Some((50, 40.0, 800))),
+            Player::sample(ShipClass::Battleship, Some((50, 50.0, 1000))),
+            Player::sample(ShipClass::Destroyer, Some((50, 60.0, 1600))),
+            Player::sample(ShipClass::Destroyer, None),
+        ];
+        let classes = summarize(&players).classes;
+        assert_eq!(
+            classes,
+            [
+                ClassSummary {
+                    class: ShipClass::Battleship,
+                    count: 1,
+                    avg_pr: Some(1000.0),
+                    avg_winrate: Some(50.0),
+                },
+                ClassSummary {
+                    class: ShipClass::Destroyer,
+                    count: 3,
+                    avg_pr: Some(1200.0),
+                    avg_winrate: Some(50.0),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_players_and_empty_teams_have_no_averages() {
+        let hidden = [Player::sample(ShipClass::Cruiser, None)];
+        let summary = summarize(&hidden);
+        assert_eq!(summary.avg_pr, None);
+        assert_eq!(summary.weighted_pr, None);
+        assert_eq!(summary.avg_account_winrate, None);
+        assert_eq!(summary.total_battles, 0);
+        assert_eq!(summary.classes.len(), 1);
+        assert_eq!(summary.classes[0].avg_pr, None);
+
+        let empty = summarize(&[]);
+        assert_eq!(empty.avg_pr, None);
+        assert_eq!(empty.unicums, 0);
+        assert!(empty.classes.is_empty());
+    }
+}
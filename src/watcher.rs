@@ -0,0 +1,96 @@
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use iced::futures::channel::mpsc;
+use iced::futures::{future, SinkExt, StreamExt};
+use iced::subscription::{self, Subscription};
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+use tracing::{error, info, warn};
+
+use crate::arena::ARENA_INFO_FILE;
+use crate::worker;
+
+// The game writes the arena file in several chunks, wait for it to settle
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+// The replays folder only exists once the game has saved its first replay, keep
+// trying with a growing delay until it shows up
+const RETRY_DELAY_MIN: Duration = Duration::from_secs(2);
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy)]
+pub enum ArenaEvent {
+    BattleStarted,
+    BattleEnded,
+}
+
+// Watch the replays folder for the arena file appearing, changing or going away.
+// The subscription is keyed by the folder, so picking another one restarts it.
+pub fn arena_events(replay_path: PathBuf) -> Subscription<ArenaEvent> {
+    subscription::channel(replay_path.clone(), 16, |mut output| async move {
+        let (sender, mut receiver) = mpsc::unbounded();
+        let debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+            let _ = sender.unbounded_send(result);
+        });
+        let mut debouncer = match debouncer {
+            Ok(debouncer) => debouncer,
+            Err(e) => {
+                error!("Failed to create folder watcher: {}", e);
+                return future::pending().await;
+            }
+        };
+
+        let mut retry_delay = RETRY_DELAY_MIN;
+        let mut retried = false;
+        while let Err(e) = debouncer
+            .watcher()
+            .watch(&replay_path, RecursiveMode::NonRecursive)
+        {
+            if !retried {
+                warn!(
+                    "Failed to watch {:?}, retrying until it is available: {}",
+                    replay_path, e
+                );
+                retried = true;
+            }
+            let delay = retry_delay;
+            worker::run(move || thread::sleep(delay)).await;
+            retry_delay = (retry_delay * 2).min(RETRY_DELAY_MAX);
+        }
+        info!("Watching {:?} for {}", replay_path, ARENA_INFO_FILE);
+
+        let arena_path = replay_path.join(ARENA_INFO_FILE);
+        // A battle may have started while the folder was not watchable yet
+        if retried && arena_path.exists() {
+            let _ = output.send(ArenaEvent::BattleStarted).await;
+        }
+        loop {
+            let events = match receiver.next().await {
+                Some(Ok(events)) => events,
+                Some(Err(e)) => {
+                    error!("Folder watcher error: {}", e);
+                    continue;
+                }
+                None => return future::pending().await,
+            };
+
+            let arena_touched = events
+                .iter()
+                .any(|event| event.path.file_name() == Some(OsStr::new(ARENA_INFO_FILE)));
+            if !arena_touched {
+                continue;
+            }
+
+            let event = if arena_path.exists() {
+                ArenaEvent::BattleStarted
+            } else {
+                ArenaEvent::BattleEnded
+            };
+            info!("Arena file changed: {:?}", event);
+            let _ = output.send(event).await;
+        }
+    })
+}
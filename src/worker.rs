@@ -0,0 +1,13 @@
+use std::thread;
+
+use iced::futures::channel::oneshot;
+
+// Run blocking work (HTTP lookups, rate limit sleeps, replay reading) on its own
+// thread, so commands waiting on it don't tie up the executor that drives the UI
+pub async fn run<T: Send + 'static>(task: impl FnOnce() -> T + Send + 'static) -> T {
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || {
+        let _ = sender.send(task());
+    });
+    receiver.await.expect("background task panicked")
+}